@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// 标记组件，覆盖层上显示的旗子
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct Flag;
@@ -0,0 +1,8 @@
+use bevy::prelude::Component;
+
+use super::Coordinates;
+
+/// 边缘相连的棋盘外面显示的对边棋子的副本，记录被复制的棋子的坐标
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Ghost(pub Coordinates);
@@ -0,0 +1,6 @@
+use bevy::prelude::Component;
+
+/// 提示组件，用于高亮被提示的棋子
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct Hint;
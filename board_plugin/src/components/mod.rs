@@ -1,9 +1,14 @@
-pub use coordinates::Coordinates;
+pub use minesweeper_core::Coordinates;
 mod bomb;
 mod bomb_neighbor;
-mod coordinates;
+mod flag;
+mod ghost;
+mod hint;
 mod uncover;
 
 pub use bomb::Bomb;
 pub use bomb_neighbor::BombNeighbor;
+pub use flag::Flag;
+pub use ghost::Ghost;
+pub use hint::Hint;
 pub use uncover::Uncover;
@@ -1,6 +1,10 @@
 use bevy::prelude::Component;
 
-/// Uncover，此组件可以揭开棋子
+/// Uncover，棋子已经揭开，等待`delay`秒后移除这个覆盖层
+/// 只用于显示揭开的动画，棋盘的状态在揭开时已经更新
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
-pub struct Uncover;
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Component)]
+pub struct Uncover {
+    /// 剩余的秒数
+    pub delay: f32,
+}
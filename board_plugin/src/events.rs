@@ -1,4 +1,4 @@
-use crate::components::Coordinates;
+use crate::{components::Coordinates, resources::BoardConfigError};
 
 #[derive(Debug, Clone, Copy)]
 pub struct TileTriggerEvent(pub Coordinates);
@@ -14,3 +14,23 @@ pub struct BombExplosionEvent;
 /// 标记
 #[derive(Debug, Clone, Copy)]
 pub struct TileMarkEvent(pub Coordinates);
+
+/// 双击已经揭开的数字棋子，翻开周围没有标记的棋子
+#[derive(Debug, Clone, Copy)]
+pub struct TileChordEvent(pub Coordinates);
+
+/// 棋盘配置无效，拒绝创建棋盘
+#[derive(Debug, Clone)]
+pub struct BoardConfigErrorEvent(pub BoardConfigError);
+
+/// 请求提示
+#[derive(Debug, Clone, Copy)]
+pub struct HintRequestedEvent;
+
+/// 提示了一个棋子
+#[derive(Debug, Clone, Copy)]
+pub struct HintShownEvent {
+    pub coordinates: Coordinates,
+    /// 这个棋子是炸弹的概率
+    pub probability: f64,
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::Vec2;
+use minesweeper_core::{Coordinates, Topology};
+
+/// 棋子在棋盘上的排列方式，坐标都相对于棋盘的左下角
+///
+/// 正方形棋盘按行列对齐；六边形棋盘的奇数行向右错开半列，
+/// 行距仍然是棋子尺寸，列距是行距的2/√3倍，这样正方形的精灵不会互相重叠
+#[derive(Debug, Clone, Copy)]
+pub struct TileLayout {
+    topology: Topology,
+    /// 棋子的尺寸，也是行距
+    tile_size: f32,
+}
+
+impl TileLayout {
+    pub fn new(topology: Topology, tile_size: f32) -> Self {
+        Self {
+            topology,
+            tile_size,
+        }
+    }
+
+    /// 相邻两列棋子中心的距离
+    fn column_width(&self) -> f32 {
+        match self.topology {
+            Topology::Square => self.tile_size,
+            Topology::Hex => self.tile_size * 2.0 / 3f32.sqrt(),
+        }
+    }
+
+    /// 这一行向右错开的距离
+    fn row_offset(&self, y: i64) -> f32 {
+        match self.topology {
+            Topology::Hex if y % 2 != 0 => self.column_width() / 2.0,
+            _ => 0.0,
+        }
+    }
+
+    /// 整个棋盘的尺寸
+    pub fn board_size(&self, (width, height): (u16, u16)) -> Vec2 {
+        let offset = if height > 1 { self.row_offset(1) } else { 0.0 };
+        Vec2::new(
+            width as f32 * self.column_width() + offset,
+            height as f32 * self.tile_size,
+        )
+    }
+
+    /// 棋子中心的位置
+    pub fn tile_center(&self, coords: Coordinates) -> Vec2 {
+        self.position((coords.x as i64, coords.y as i64))
+    }
+
+    /// 坐标中心的位置，坐标可以在棋盘外，比如边缘相连的棋盘四周的副本
+    pub fn position(&self, (x, y): (i64, i64)) -> Vec2 {
+        let column_width = self.column_width();
+        Vec2::new(
+            x as f32 * column_width + self.row_offset(y) + column_width / 2.0,
+            y as f32 * self.tile_size + self.tile_size / 2.0,
+        )
+    }
+
+    /// 位置所在的棋子，不在棋盘内时返回`None`
+    pub fn tile_at(&self, position: Vec2, (width, height): (u16, u16)) -> Option<Coordinates> {
+        let size = self.board_size((width, height));
+        if position.x < 0.0 || position.y < 0.0 || position.x >= size.x || position.y >= size.y {
+            return None;
+        }
+        let column_width = self.column_width();
+        let row = (position.y / self.tile_size) as i64;
+        match self.topology {
+            Topology::Square => Some(Coordinates {
+                x: (position.x / column_width) as u16,
+                y: row as u16,
+            }),
+            // 六边形就是离中心最近的区域，在相邻的几行中找离位置最近的棋子
+            Topology::Hex => (row - 1..=row + 1)
+                .filter(|y| (0..height as i64).contains(y))
+                .map(|y| {
+                    let x = ((position.x - self.row_offset(y)) / column_width).floor() as i64;
+                    Coordinates {
+                        x: x.clamp(0, width as i64 - 1) as u16,
+                        y: y as u16,
+                    }
+                })
+                .min_by(|a, b| {
+                    let da = self.tile_center(*a).distance_squared(position);
+                    let db = self.tile_center(*b).distance_squared(position);
+                    da.total_cmp(&db)
+                }),
+        }
+    }
+}
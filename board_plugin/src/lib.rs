@@ -1,66 +1,126 @@
+use std::fs;
+use std::path::Path;
+
 use bevy::ecs::schedule::StateData;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::Plugin;
 use bevy::utils::AHashExt;
 use bevy::utils::HashMap;
-use resources::tile::Tile;
 use resources::BoardAssets;
 use resources::BoardOptions;
 
 pub mod components;
+pub mod events;
 pub mod resources;
 
+pub use minesweeper_core::solver;
+
 mod bounds;
-mod events;
+mod layout;
+mod mask;
 mod systems;
 
 use crate::bounds::Bounds2;
 use crate::components::Bomb;
 use crate::components::BombNeighbor;
 use crate::components::Coordinates;
-use crate::components::Uncover;
+use crate::components::Ghost;
 use crate::events::BoardCompletedEvent;
+use crate::events::BoardConfigErrorEvent;
 use crate::events::BombExplosionEvent;
+use crate::events::HintRequestedEvent;
+use crate::events::HintShownEvent;
+use crate::events::TileChordEvent;
 use crate::events::TileMarkEvent;
 use crate::events::TileTriggerEvent;
-use crate::resources::tile_map::TileMap;
+use crate::layout::TileLayout;
+use crate::resources::BoardConfigError;
 use crate::resources::BoardPosition;
+use crate::resources::BoardShape;
+use crate::resources::BoardSource;
 use crate::resources::TileSize;
 use bevy::log;
 use bevy::prelude::*;
 
+use minesweeper_core::{BoardFormat, Game, Tile, TileMap};
 use resources::board::Board;
+use resources::{GameStats, ReplayRecorder, SaveGame};
+use systems::ghost::{ghost_color, ghost_material};
+use systems::mark::spawn_flag;
 
 use bevy::math::Vec3Swizzles;
 
+/// 需要确定执行顺序的系统
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+enum BoardSystem {
+    /// 处理玩家输入或者回放录像
+    Input,
+    /// 把双击转换成翻开棋子的事件
+    Chord,
+    /// 放置炸弹
+    Populate,
+}
+
 pub struct BoardPlugin<T> {
     pub running_state: T,
+    /// 游戏胜利或者失败后进入的状态，会压入状态栈，此时棋盘仍然保留，但是不再处理输入
+    pub end_state: T,
 }
 
+/// 游戏结束后进入的状态
+struct EndState<T>(T);
+
 impl<T: StateData> Plugin for BoardPlugin<T> {
     fn build(&self, app: &mut App) {
         // on_enter 当进入栈的时候，执行
         // on_update 处理输入，当状态处于活动状态的时候，触发对应的处理事件，允许暂停状态
         // on_in_stack_update uncover系统不应该暂停，所以如果状态在栈中，则不管是否处于活动状态，都要运行
         app.add_system_set(
-            SystemSet::on_enter(self.running_state.clone()).with_system(Self::create_board),
+            SystemSet::on_enter(self.running_state.clone())
+                .with_system(Self::create_board)
+                .with_system(systems::replay::rewind_replay),
         )
         .add_system_set(
             SystemSet::on_update(self.running_state.clone())
-                .with_system(systems::input::input_handling)
-                .with_system(systems::uncover::trigger_event_handler),
+                .with_system(systems::input::input_handling.label(BoardSystem::Input))
+                .with_system(systems::replay::play_actions.label(BoardSystem::Input))
+                .with_system(systems::replay::tick_recorder.before(BoardSystem::Input))
+                .with_system(systems::hint::hint_handler)
+                .with_system(systems::timer::tick_timer)
+                .with_system(
+                    systems::uncover::chord_handler
+                        .label(BoardSystem::Chord)
+                        .after(BoardSystem::Input),
+                )
+                .with_system(Self::populate_board.label(BoardSystem::Populate))
+                .with_system(
+                    systems::uncover::trigger_event_handler
+                        .after(BoardSystem::Chord)
+                        .after(BoardSystem::Populate),
+                ),
         )
         .add_system_set(
             SystemSet::on_in_stack_update(self.running_state.clone())
                 .with_system(systems::uncover::uncover_tiles)
-                .with_system(systems::mark::mark_tiles),
+                .with_system(systems::mark::mark_tiles)
+                .with_system(systems::ghost::update_ghosts)
+                .with_system(systems::outcome::game_lost)
+                .with_system(systems::stats::publish_stats)
+                .with_system(Self::end_game),
         )
         .add_system_set(
             SystemSet::on_exit(self.running_state.clone()).with_system(Self::cleanup_board),
         )
         .add_event::<TileTriggerEvent>()
         .add_event::<TileMarkEvent>()
+        .add_event::<TileChordEvent>()
         .add_event::<BombExplosionEvent>()
-        .add_event::<BoardCompletedEvent>();
+        .add_event::<BoardCompletedEvent>()
+        .add_event::<HintRequestedEvent>()
+        .add_event::<HintShownEvent>()
+        .add_event::<BoardConfigErrorEvent>()
+        .add_event::<GameStats>()
+        .insert_resource(EndState(self.end_state.clone()));
 
         log::info!("面板已加载");
         #[cfg(feature = "debug")]
@@ -71,6 +131,28 @@ impl<T: StateData> Plugin for BoardPlugin<T> {
             app.register_inspectable::<BombNeighbor>();
             app.register_inspectable::<Bomb>();
             app.register_inspectable::<Uncover>();
+            app.register_inspectable::<components::Flag>();
+            app.register_inspectable::<components::Hint>();
+            app.register_inspectable::<components::Ghost>();
+        }
+    }
+}
+
+impl<T: StateData> BoardPlugin<T> {
+    /// 游戏胜利或者失败后，切换到结束状态
+    fn end_game(
+        mut state: ResMut<State<T>>,
+        end_state: Res<EndState<T>>,
+        mut board_completed_evr: EventReader<BoardCompletedEvent>,
+        mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    ) {
+        let completed = board_completed_evr.iter().count() > 0;
+        let exploded = bomb_explosion_evr.iter().count() > 0;
+        if !(completed || exploded) || state.current() == &end_state.0 {
+            return;
+        }
+        if let Err(e) = state.push(end_state.0.clone()) {
+            log::error!("切换到结束状态失败: {:?}", e);
         }
     }
 }
@@ -80,35 +162,51 @@ impl<T> BoardPlugin<T> {
     pub fn create_board(
         mut commands: Commands,
         board_options: Option<Res<BoardOptions>>,
+        save_game: Option<Res<SaveGame>>,
         board_assets: Res<BoardAssets>,
         window: Res<WindowDescriptor>,
+        mut board_config_error_ewr: EventWriter<BoardConfigErrorEvent>,
     ) {
-        let options = match board_options {
-            None => BoardOptions::default(),
-            Some(o) => o.clone(),
+        // 存档只使用一次，之后重新开始会生成新的棋盘
+        let save_game = save_game.map(|save| {
+            commands.remove_resource::<SaveGame>();
+            save.clone()
+        });
+        let mut options = match (&save_game, board_options) {
+            (Some(save), _) => save.options.clone(),
+            (None, None) => BoardOptions::default(),
+            (None, Some(o)) => o.clone(),
         };
-        // 创建空的棋盘，并且在其上放置棋子
-        let mut tile_map = TileMap::empty(options.map_size.0, options.map_size.1);
-        tile_map.set_bombs(options.bomb_count);
-
-        // 如果编译时指定 --features debug，则会执行这一句
-        #[cfg(feature = "debug")]
-        log::info!("棋盘: {}", tile_map.console_output());
+        // 棋盘文件和遮罩决定尺寸时，尺寸和炸弹数量在`new_game`读取之后才检查
+        let created = options.validate().and_then(|_| match &save_game {
+            Some(save) => {
+                log::info!("从存档恢复棋盘，种子: {}", save.game.seed());
+                Ok(save.game.clone())
+            }
+            None => Self::new_game(&mut options),
+        });
+        // 无效的配置不会创建棋盘，由使用者处理这个事件
+        let game = match created {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("棋盘配置无效: {}", e);
+                board_config_error_ewr.send(BoardConfigErrorEvent(e));
+                return;
+            }
+        };
+        let tile_map = game.tile_map();
 
+        let map_size = (tile_map.width(), tile_map.height());
         let tile_size = match options.tile_size {
             TileSize::Fixed(v) => v,
-            TileSize::Adaptive { min, max } => Self::adaptative_tile_size(
-                window,
-                (min, max),
-                (tile_map.width(), tile_map.height()),
-            ),
+            TileSize::Adaptive { min, max } => {
+                Self::adaptative_tile_size(window, (min, max), tile_map)
+            }
         };
+        let layout = TileLayout::new(tile_map.topology(), tile_size);
 
         // 计算面板的大小
-        let board_size = Vec2::new(
-            tile_map.width() as f32 * tile_size,
-            tile_map.height() as f32 * tile_size,
-        );
+        let board_size = layout.board_size(map_size);
         log::info!("面板尺寸: {}", board_size);
         let board_position = match options.position {
             BoardPosition::Centered { offset } => {
@@ -119,7 +217,6 @@ impl<T> BoardPlugin<T> {
 
         let mut covered_tiles =
             HashMap::with_capacity((tile_map.width() * tile_map.height()).into());
-        let mut safe_start = None;
 
         let board_entity = commands
             .spawn()
@@ -143,26 +240,54 @@ impl<T> BoardPlugin<T> {
 
                 Self::spawn_tiles(
                     parent,
-                    &tile_map,
+                    &game,
+                    layout,
                     tile_size,
                     options.tile_padding,
                     &board_assets,
                     &mut covered_tiles,
-                    &mut safe_start,
-                )
+                );
+                if game.tile_map().wraps() {
+                    Self::spawn_ghosts(
+                        parent,
+                        &game,
+                        layout,
+                        tile_size - options.tile_padding,
+                        &board_assets,
+                    );
+                }
             })
             .id();
 
-        // 如果开启了这个选项，则使用spawn_tiles函数改过的safe_start
-        // 添加Uncover组件，用于揭开这个空白棋子
-        if options.safe_start {
-            if let Some(entity) = safe_start {
-                commands.entity(entity).insert(Uncover);
+        // 从存档恢复的游戏无法从头复现，不录制
+        if save_game.is_none() {
+            commands.insert_resource(ReplayRecorder::new(BoardOptions {
+                seed: Some(game.seed()),
+                ..options.clone()
+            }));
+        } else {
+            commands.remove_resource::<ReplayRecorder>();
+        }
+
+        // 恢复存档中的标记
+        for coords in game.marked() {
+            if let Some(entity) = covered_tiles.get(&coords) {
+                let count = game.mark_count_at(coords);
+                spawn_flag(&mut commands, *entity, tile_size, count, &board_assets);
             }
         }
 
+        // 没有放置炸弹的时候，3BV在第一次翻开棋子时计算
+        let bbbv = if game.is_generated() {
+            game.tile_map().bbbv()
+        } else {
+            0
+        };
+        // 上一局游戏的统计
+        commands.remove_resource::<GameStats>();
+
         commands.insert_resource(Board {
-            tile_map,
+            game,
             bounds: Bounds2 {
                 position: board_position.xy(),
                 size: board_size,
@@ -170,57 +295,174 @@ impl<T> BoardPlugin<T> {
             tile_size,
             covered_tiles,
             entity: board_entity,
-            marked_tiles: Vec::new(),
+            hints_used: save_game.as_ref().map_or(0, |save| save.hints_used),
+            elapsed: save_game.as_ref().map_or(0., |save| save.elapsed),
+            // 存档中的计时器已经开始，说明玩家翻开过棋子
+            started: save_game.as_ref().is_some_and(|save| save.elapsed > 0.),
+            bbbv,
+            clicks: save_game
+                .as_ref()
+                .map_or_else(Default::default, |save| save.clicks),
+            // 旧的存档中没有这一项，有标记的棋子时一定放置过旗子
+            flagged: save_game
+                .as_ref()
+                .is_some_and(|save| save.flagged || save.game.marked().next().is_some()),
+            options,
         })
     }
 
+    /// 使用配置创建新的一局游戏，炸弹会在第一次翻开棋子的时候才放置
+    /// 开启了`safe_start`时，以棋盘中心为起点立即放置炸弹并揭开中心的棋子
+    fn new_game(options: &mut BoardOptions) -> Result<Game, BoardConfigError> {
+        // 相同的种子、棋盘尺寸、炸弹数量和第一次翻开的坐标总是生成相同的棋盘
+        let seed = options.seed.unwrap_or_else(rand::random);
+        log::info!("棋盘种子: {}", seed);
+
+        if let BoardSource::File(path) = &options.source {
+            let tile_map = Self::read_board_file(path)?;
+            log::info!("从文件读取棋盘: {}", path.display());
+            // 棋盘大小和炸弹数量以文件为准
+            options.map_size = (tile_map.width(), tile_map.height());
+            options.bomb_count = tile_map.bomb_count();
+            options.validate_wrap()?;
+            // 文件中的炸弹位置是固定的，没有安全的起点
+            let game = Game::from_tile_map(tile_map, seed)
+                .with_topology(options.topology)
+                .with_wrap(options.wrap);
+            options.mines_per_tile = game.mines_per_tile();
+            return Ok(game);
+        }
+
+        // 棋盘大小以遮罩为准，炸弹数量要按照去掉空洞后的棋子数量检查
+        let mask = match &options.shape {
+            BoardShape::Rectangle => None,
+            BoardShape::Mask(path) => {
+                let mask = mask::read_mask(path)?;
+                log::info!("从文件读取棋盘形状: {}", path.display());
+                options.map_size = (mask.width(), mask.height());
+                options.validate_wrap()?;
+                Some(mask)
+            }
+        };
+
+        let mut game = Game::new(
+            options.map_size,
+            options.bomb_count,
+            options.generation.clone(),
+            seed,
+        )
+        .with_topology(options.topology)
+        .with_wrap(options.wrap)
+        .with_mines_per_tile(options.mines_per_tile);
+        if let Some(mask) = mask {
+            game = game
+                .with_void(mask.void())
+                .expect("遮罩和棋盘的尺寸相同，而且还没有放置炸弹");
+        }
+        // 有空洞的棋盘只有遮罩读取之后才知道棋子的数量，需要再检查一次炸弹数量
+        game.validate()?;
+        if !options.safe_start {
+            return Ok(game);
+        }
+        // 中心可能是空洞，从离中心最近的棋子开始
+        if let Some((start, _)) = game.hint() {
+            game.reveal(start);
+        }
+
+        #[cfg(feature = "debug")]
+        log::info!("棋盘: {}", game.tile_map().console_output());
+        Ok(game)
+    }
+
+    /// 从文件读取棋盘
+    fn read_board_file(path: &Path) -> Result<TileMap, BoardConfigError> {
+        let error = |reason: String| BoardConfigError::InvalidBoardFile {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let data = fs::read(path).map_err(|e| error(e.to_string()))?;
+        TileMap::read(&data, BoardFormat::from_path(path)).map_err(|e| error(e.to_string()))
+    }
+
+    /// 第一次翻开棋子的时候，以这个棋子为中心放置炸弹，并填充棋子的内容
+    pub fn populate_board(
+        mut commands: Commands,
+        board: Option<ResMut<Board>>,
+        board_assets: Res<BoardAssets>,
+        mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+        tiles: Query<(Entity, &Coordinates)>,
+    ) {
+        let mut board = match board {
+            Some(b) => b,
+            None => return,
+        };
+        for trigger_event in tile_trigger_evr.iter() {
+            if board.game.is_generated() {
+                break;
+            }
+            if let Err(e) = board.game.generate(trigger_event.0) {
+                log::error!("无法放置炸弹: {}", e);
+                break;
+            }
+            board.bbbv = board.game.tile_map().bbbv();
+
+            // 如果编译时指定 --features debug，则会执行这一句
+            #[cfg(feature = "debug")]
+            log::info!("棋盘: {}", board.game.tile_map().console_output());
+
+            let size = board.tile_size - board.options.tile_padding;
+            for (entity, coords) in tiles.iter() {
+                let tile = &board.game.tile_map()[*coords];
+                Self::spawn_tile_content(&mut commands.entity(entity), tile, size, &board_assets);
+            }
+        }
+    }
+
     ///
     fn adaptative_tile_size(
         window: Res<WindowDescriptor>,
         (min, max): (f32, f32),
-        (width, height): (u16, u16),
+        tile_map: &TileMap,
     ) -> f32 {
-        let max_width = window.width / width as f32;
-        let max_height = window.height / height as f32;
+        // 边缘相连时，四周还要显示一圈副本
+        let margin = if tile_map.wraps() { 2 } else { 0 };
+        let map_size = (tile_map.width() + margin, tile_map.height() + margin);
+        // 棋子尺寸为1时棋盘的尺寸
+        let unit = TileLayout::new(tile_map.topology(), 1.0).board_size(map_size);
+        let max_width = window.width / unit.x;
+        let max_height = window.height / unit.y;
         max_width.min(max_height).clamp(min, max)
     }
 
     #[allow(clippy::too_many_arguments)]
     fn spawn_tiles(
         parent: &mut ChildBuilder,
-        tile_map: &TileMap,
+        game: &Game,
+        layout: TileLayout,
         size: f32,
         padding: f32,
         board_assets: &BoardAssets,
         covered_tiles: &mut HashMap<Coordinates, Entity>,
-        safe_start_entity: &mut Option<Entity>,
     ) {
-        for (y, line) in tile_map.iter().enumerate() {
-            for (x, tile) in line.iter().enumerate() {
-                let coordinates = Coordinates {
-                    x: x as u16,
-                    y: y as u16,
-                };
-                let mut cmd = parent.spawn();
+        for (coordinates, tile) in game.tile_map().tiles() {
+            let (x, y) = (coordinates.x, coordinates.y);
+            let mut cmd = parent.spawn();
 
-                cmd.insert_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: board_assets.tile_material.color,
-                        custom_size: Some(Vec2::splat(size - padding as f32)),
-                        ..Default::default()
-                    },
-                    texture: board_assets.tile_material.texture.clone(),
-                    transform: Transform::from_xyz(
-                        (x as f32 * size) + size / 2.0,
-                        (y as f32 * size) + size / 2.0,
-                        3.0,
-                    ),
+            cmd.insert_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: board_assets.tile_material.color,
+                    custom_size: Some(Vec2::splat(size - padding as f32)),
                     ..Default::default()
-                })
-                .insert(Name::new(format!("Tile ({}, {})", x, y)))
-                .insert(coordinates);
+                },
+                texture: board_assets.tile_material.texture.clone(),
+                transform: Transform::from_translation(layout.tile_center(coordinates).extend(3.0)),
+                ..Default::default()
+            })
+            .insert(Name::new(format!("Tile ({}, {})", x, y)))
+            .insert(coordinates);
 
-                // 覆盖棋子
+            // 覆盖还没有揭开的棋子，从存档恢复时部分棋子已经揭开
+            if game.is_covered_at(coordinates) {
                 cmd.with_children(|parent| {
                     let mut cmd = parent.spawn();
                     let entity = cmd
@@ -238,66 +480,99 @@ impl<T> BoardPlugin<T> {
                         .id();
 
                     covered_tiles.insert(coordinates, entity);
-
-                    if safe_start_entity.is_none() && *tile == Tile::Empty {
-                        *safe_start_entity = Some(entity);
-                    }
                 });
+            }
 
-                match tile {
-                    Tile::Bomb => {
-                        cmd.insert(Bomb);
-                        cmd.with_children(|parent| {
-                            parent.spawn_bundle(SpriteBundle {
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::splat(size - padding)),
-                                    ..Default::default()
-                                },
-                                transform: Transform::from_xyz(0.0, 0.0, 1.0),
-                                texture: board_assets.bomb_material.texture.clone(),
-                                ..Default::default()
-                            });
-                        });
-                    }
-                    Tile::BombNeighbor(v) => {
-                        cmd.insert(BombNeighbor { count: *v });
-                        cmd.with_children(|parent| {
-                            parent.spawn_bundle(Self::bomb_count_text_bundle(
-                                *v,
-                                board_assets,
-                                size - padding,
-                            ));
-                        });
-                    }
-                    Tile::Empty => (),
+            // 还没有放置炸弹的时候，所有棋子都是空的，不会生成任何内容
+            Self::spawn_tile_content(&mut cmd, tile, size - padding, board_assets);
+        }
+    }
+
+    /// 边缘相连的棋盘四周显示一圈对边棋子的半透明副本，让玩家看出棋盘是相连的
+    fn spawn_ghosts(
+        parent: &mut ChildBuilder,
+        game: &Game,
+        layout: TileLayout,
+        size: f32,
+        board_assets: &BoardAssets,
+    ) {
+        let (width, height) = (
+            game.tile_map().width() as i64,
+            game.tile_map().height() as i64,
+        );
+        for y in -1..=height {
+            for x in -1..=width {
+                if (0..width).contains(&x) && (0..height).contains(&y) {
+                    continue;
+                }
+                let source = Coordinates {
+                    x: x.rem_euclid(width) as u16,
+                    y: y.rem_euclid(height) as u16,
+                };
+                if game.tile_map().is_void_at(source) {
+                    continue;
                 }
+                let material = ghost_material(game, board_assets, source);
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(size)),
+                            color: ghost_color(material),
+                            ..Default::default()
+                        },
+                        texture: material.texture.clone(),
+                        transform: Transform::from_translation(layout.position((x, y)).extend(3.0)),
+                        ..Default::default()
+                    })
+                    .insert(Name::new(format!("Ghost ({}, {})", source.x, source.y)))
+                    .insert(Ghost(source));
             }
         }
     }
 
-    fn bomb_count_text_bundle(count: u8, board_assets: &BoardAssets, size: f32) -> Text2dBundle {
-        let color = board_assets.bomb_counter_color(count);
-        Text2dBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: count.to_string(),
-                    style: TextStyle {
-                        color,
-                        font: board_assets.bomb_counter_font.clone(),
-                        font_size: size,
-                    },
-                }],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            transform: Transform::from_xyz(0.0, 0.0, 1.0),
-            ..Default::default()
+    /// 根据棋子的类型，给棋子添加炸弹或者炸弹计数
+    fn spawn_tile_content(
+        cmd: &mut EntityCommands,
+        tile: &Tile,
+        size: f32,
+        board_assets: &BoardAssets,
+    ) {
+        match tile {
+            Tile::Bomb(count) => {
+                cmd.insert(Bomb);
+                cmd.with_children(|parent| {
+                    parent.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(size)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                        texture: board_assets.bomb_material.texture.clone(),
+                        ..Default::default()
+                    });
+                    // 有多个炸弹时，在炸弹上显示数量
+                    if *count > 1 {
+                        let mut text = board_assets.count_text_bundle(*count, size / 2.0);
+                        text.transform.translation.z = 2.0;
+                        parent.spawn_bundle(text);
+                    }
+                });
+            }
+            Tile::BombNeighbor(v) => {
+                cmd.insert(BombNeighbor { count: *v });
+                cmd.with_children(|parent| {
+                    parent.spawn_bundle(board_assets.count_text_bundle(*v, size));
+                });
+            }
+            Tile::Empty => (),
         }
     }
 
-    fn cleanup_board(board: Res<Board>, mut commands: Commands) {
+    fn cleanup_board(board: Option<Res<Board>>, mut commands: Commands) {
+        let board = match board {
+            Some(b) => b,
+            None => return,
+        };
         // 清理所有的棋子
         commands.entity(board.entity).despawn_recursive();
         commands.remove_resource::<Board>();
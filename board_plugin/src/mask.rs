@@ -0,0 +1,61 @@
+use std::{fs, path::Path};
+
+use bevy::render::{
+    render_resource::TextureFormat,
+    texture::{Image, ImageType},
+};
+use minesweeper_core::{BoardFormat, Coordinates, TileMap};
+
+use crate::resources::BoardConfigError;
+
+/// 读取棋盘的形状，返回只有空洞、没有炸弹的棋盘
+/// `.txt`文件按照文本格式读取，`#`是空洞，其中的炸弹会被忽略；其他文件当做图片
+pub fn read_mask(path: &Path) -> Result<TileMap, BoardConfigError> {
+    let error = |reason: String| BoardConfigError::InvalidMask {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let data = fs::read(path).map_err(|e| error(e.to_string()))?;
+    if BoardFormat::from_path(path) == BoardFormat::Text {
+        let layout = TileMap::read(&data, BoardFormat::Text).map_err(|e| error(e.to_string()))?;
+        return TileMap::empty(layout.width(), layout.height())
+            .with_void(layout.void())
+            .map_err(|e| error(e.to_string()));
+    }
+
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let image = Image::from_buffer(&data, ImageType::Extension(extension))
+        .map_err(|e| error(e.to_string()))?;
+    let size = image.texture_descriptor.size;
+    if size.width > u16::MAX as u32 || size.height > u16::MAX as u32 {
+        return Err(error(format!("图片尺寸{}x{}太大", size.width, size.height)));
+    }
+    let (width, height) = (size.width as u16, size.height as u16);
+    if width == 0 || height == 0 {
+        return Err(error("图片是空的".to_string()));
+    }
+
+    // 每个像素的字节数，以及像素是不是空洞
+    let (stride, is_void): (usize, fn(&[u8]) -> bool) = match image.texture_descriptor.format {
+        TextureFormat::R8Unorm => (1, |p| p[0] < 128),
+        TextureFormat::Rg8Unorm => (2, |p| p[0] < 128 || p[1] < 128),
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => (4, |p| {
+            let luma = (p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114) / 1000;
+            luma < 128 || p[3] < 128
+        }),
+        format => return Err(error(format!("不支持的像素格式{:?}", format))),
+    };
+    // 图片的第一行是最上面一排，而棋盘的y轴向上
+    let void = image
+        .data
+        .chunks_exact(stride)
+        .enumerate()
+        .filter(|(_, pixel)| is_void(pixel))
+        .map(|(i, _)| Coordinates {
+            x: (i % width as usize) as u16,
+            y: height - 1 - (i / width as usize) as u16,
+        });
+    TileMap::empty(width, height)
+        .with_void(void)
+        .map_err(|e| error(e.to_string()))
+}
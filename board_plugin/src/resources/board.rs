@@ -1,17 +1,34 @@
-use bevy::{log, prelude::*, utils::HashMap};
+use bevy::{prelude::*, utils::HashMap};
+pub use minesweeper_core::GameOutcome;
+use minesweeper_core::{Game, RevealOutcome};
 
-use crate::{bounds::Bounds2, components::Coordinates};
+use crate::{bounds::Bounds2, components::Coordinates, layout::TileLayout};
 
-use super::tile_map::TileMap;
+use super::{BoardOptions, Clicks};
 
 #[derive(Debug)]
 pub struct Board {
-    pub tile_map: TileMap,
+    /// 游戏规则和状态，棋盘只负责把它画出来
+    pub game: Game,
     pub bounds: Bounds2,
     pub tile_size: f32,
+    /// 还没有揭开的棋子上的覆盖层
     pub covered_tiles: HashMap<Coordinates, Entity>,
     pub entity: Entity,
-    pub marked_tiles: Vec<Coordinates>,
+    /// 生成棋盘时使用的配置
+    pub options: BoardOptions,
+    /// 使用提示的次数
+    pub hints_used: u32,
+    /// 游戏进行的秒数，从第一次翻开棋子开始计时，游戏结束时停止
+    pub elapsed: f32,
+    /// 玩家是否已经翻开过棋子，`safe_start`自动翻开的棋子不算
+    pub started: bool,
+    /// 棋盘的3BV，放置炸弹之后才能计算
+    pub bbbv: u32,
+    /// 玩家的点击次数
+    pub clicks: Clicks,
+    /// 是否放置过旗子，取消标记后也不会改变
+    pub flagged: bool,
 }
 
 impl Board {
@@ -25,81 +42,80 @@ impl Board {
             return None;
         }
 
-        let coordinates = position - self.bounds.position;
-        Some(Coordinates {
-            x: (coordinates.x / self.tile_size) as u16,
-            y: (coordinates.y / self.tile_size) as u16,
-        })
+        let tile_map = self.game.tile_map();
+        self.layout().tile_at(
+            position - self.bounds.position,
+            (tile_map.width(), tile_map.height()),
+        )
+    }
+
+    /// 棋子的排列方式
+    pub(crate) fn layout(&self) -> TileLayout {
+        TileLayout::new(self.game.tile_map().topology(), self.tile_size)
     }
 
     pub fn is_covered_at(&self, coords: Coordinates) -> bool {
-        self.covered_tiles.get(&coords).is_some()
+        self.game.is_covered_at(coords)
     }
 
     pub fn is_marked_at(&self, coords: Coordinates) -> bool {
-        self.marked_tiles.contains(&coords)
+        self.game.is_marked_at(coords)
     }
 
     pub fn is_bomb_at(&self, coords: Coordinates) -> bool {
-        self.tile_map.is_bomb_at(coords)
+        self.game.is_bomb_at(coords)
     }
 
-    pub fn is_completed(&self) -> bool {
-        self.tile_map.bomb_count() as usize == self.covered_tiles.len()
+    /// 剩余的炸弹数量，标记过多时为负数
+    pub fn remaining_mines(&self) -> i32 {
+        self.game.remaining_mines()
     }
 
-    /// 隐藏棋子
-    pub fn tile_to_uncover(&self, coords: &Coordinates) -> Option<&Entity> {
-        // 如果这个棋子已经被标记，则忽略
-        if self.marked_tiles.contains(coords) {
-            None
-        } else {
-            self.covered_tiles.get(coords)
-        }
+    pub fn is_playing(&self) -> bool {
+        self.game.is_playing()
     }
 
-    /// 揭开棋子
-    pub fn try_uncover_tile(&mut self, coords: &Coordinates) -> Option<Entity> {
-        // 如果棋子被标记了，则先去除标记
-        if self.marked_tiles.contains(coords) {
-            self.unmarked_tile(coords)?;
-        }
-        self.covered_tiles.remove(coords)
+    /// 提示一个棋子，优先选择一定不是炸弹的棋子，否则选择是炸弹的概率最低的棋子
+    pub fn hint(&self) -> Option<(Coordinates, f64)> {
+        self.game.hint()
     }
 
-    /// 检测当前棋子周围有没有隐藏的棋子
-    pub fn adjacent_covered_tiles(&self, coord: Coordinates) -> Vec<Entity> {
-        self.tile_map
-            .safe_square_at(coord)
-            .filter_map(|c| self.covered_tiles.get(&c))
-            .copied()
-            .collect()
+    /// 使用提示增加的秒数
+    pub fn hint_penalty(&self) -> f32 {
+        self.hints_used as f32 * self.options.hint_penalty
     }
 
-    /// 移除这个棋子身上的标记
-    fn unmarked_tile(&mut self, coords: &Coordinates) -> Option<Coordinates> {
-        // 查找是否有坐标一样的标记棋子
-        let pos = match self.marked_tiles.iter().position(|a| a == coords) {
-            None => {
-                log::error!("移除标记失败: {}", coords);
-                return None;
+    /// 揭开棋子，返回揭开的结果，以及所有被揭开的棋子的覆盖层
+    /// 覆盖层已经不在`covered_tiles`中，由调用者销毁
+    pub fn reveal(&mut self, coords: Coordinates) -> (RevealOutcome, Vec<(Coordinates, Entity)>) {
+        let outcome = self.game.reveal(coords);
+        let revealed = match &outcome {
+            RevealOutcome::Ignored => Vec::new(),
+            RevealOutcome::Exploded => vec![coords],
+            RevealOutcome::Revealed { revealed } | RevealOutcome::Won { revealed, .. } => {
+                revealed.clone()
             }
-            Some(p) => p,
         };
-        Some(self.marked_tiles.remove(pos))
+        let covers = revealed
+            .into_iter()
+            .filter_map(|c| self.covered_tiles.remove(&c).map(|entity| (c, entity)))
+            .collect();
+        (outcome, covers)
     }
 
-    /// 尝试标记一个棋子，如果棋子已经被标记，则取消标记并返回false
-    /// 如果棋子未被标记，则标记并返回true
-    pub fn try_toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, bool)> {
+    /// 双击一个已经揭开的数字棋子，返回需要揭开的棋子
+    pub fn chord_tiles(&self, coords: Coordinates) -> Vec<Coordinates> {
+        self.game.chord(coords)
+    }
+
+    /// 尝试在一个棋子上增加一个旗子，返回覆盖层和标记后旗子的数量
+    /// 旗子的数量超过每个棋子上最多的炸弹数量时取消标记，返回0
+    pub fn toggle_mark(&mut self, coords: &Coordinates) -> Option<(Entity, u8)> {
         let entity = *self.covered_tiles.get(coords)?;
-        let mark = if self.marked_tiles.contains(coords) {
-            self.unmarked_tile(coords)?;
-            false
-        } else {
-            self.marked_tiles.push(*coords);
-            true
-        };
+        let mark = self.game.toggle_mark(*coords)?;
+        if mark > 0 {
+            self.flagged = true;
+        }
         Some((entity, mark))
     }
 }
@@ -1,4 +1,5 @@
 use bevy::{prelude::*, render::texture::DEFAULT_IMAGE_HANDLE};
+use minesweeper_core::Tile;
 
 #[derive(Debug, Clone)]
 pub struct SpriteMaterial {
@@ -25,18 +26,30 @@ pub struct BoardAssets {
     pub bomb_counter_colors: Vec<Color>,
     pub flag_material: SpriteMaterial,
     pub bomb_material: SpriteMaterial,
+    pub hint_material: SpriteMaterial,
+    /// 失败时，爆炸的棋子
+    pub exploded_material: SpriteMaterial,
+    /// 失败时，在错误的标记上画叉
+    pub wrong_flag_material: SpriteMaterial,
 }
 
 impl BoardAssets {
-    /// 用于炸弹计数的棋子
+    /// 数字的最大值：8个邻居，每个邻居上都有最多的炸弹
+    pub const MAX_COUNTER: u8 = 8 * Tile::MAX_MINES;
+
+    /// 用于炸弹计数的棋子，覆盖1到`MAX_COUNTER`
     pub fn default_colors() -> Vec<Color> {
-        vec![
+        let mut colors = vec![
             Color::WHITE,
             Color::GREEN,
             Color::YELLOW,
             Color::ORANGE,
             Color::PURPLE,
-        ]
+        ];
+        // 一个棋子上有多个炸弹时数字可以超过8，更大的数字按照色相从红色变化到品红色
+        let extra = Self::MAX_COUNTER as usize - colors.len();
+        colors.extend((0..extra).map(|i| Color::hsl(300.0 * i as f32 / extra as f32, 1.0, 0.6)));
+        colors
     }
 
     pub fn bomb_counter_color(&self, counter: u8) -> Color {
@@ -46,4 +59,26 @@ impl BoardAssets {
             None => Color::WHITE,
         }
     }
+
+    /// 显示数量的文字，比如炸弹计数和多个旗子的数量
+    pub fn count_text_bundle(&self, count: u8, size: f32) -> Text2dBundle {
+        Text2dBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value: count.to_string(),
+                    style: TextStyle {
+                        color: self.bomb_counter_color(count),
+                        font: self.bomb_counter_font.clone(),
+                        font_size: size,
+                    },
+                }],
+                alignment: TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..Default::default()
+        }
+    }
 }
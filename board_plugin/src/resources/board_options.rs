@@ -1,4 +1,12 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
 use bevy::prelude::Vec3;
+pub use minesweeper_core::{Difficulty, Generation, Tile, Topology};
+use minesweeper_core::{Game, GameError};
 use serde::{Deserialize, Serialize};
 
 /// TileSize
@@ -19,6 +27,28 @@ pub enum BoardPosition {
     Custom(Vec3),
 }
 
+/// 棋盘的来源：按照种子随机生成，或者从棋盘文件读取炸弹的位置
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub enum BoardSource {
+    /// 按照`generation`随机放置炸弹
+    #[default]
+    Random,
+    /// 从文件读取炸弹的位置，格式由扩展名决定：`.mbf`、`.txt`，其他的当做棋盘代码
+    /// 棋盘大小和炸弹数量以文件为准
+    File(PathBuf),
+}
+
+/// 棋盘的形状：完整的矩形，或者按照遮罩图片或文本去掉空洞后的形状
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub enum BoardShape {
+    /// 完整的矩形棋盘
+    #[default]
+    Rectangle,
+    /// 按照遮罩去掉一部分棋子，棋盘大小以遮罩为准
+    /// `.txt`文件中`#`是空洞，其他文件当做图片，透明或者黑色的像素是空洞
+    Mask(PathBuf),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoardOptions {
     /// 棋盘大小
@@ -33,6 +63,227 @@ pub struct BoardOptions {
     pub tile_padding: f32,
     /// 控制初始是否先揭开一个空白的棋子
     pub safe_start: bool,
+    /// 生成棋盘的随机数种子，为`None`时随机选择一个种子
+    /// 炸弹在第一次翻开棋子时才放置，种子和第一次翻开的坐标一起决定棋盘，开启`safe_start`时只由种子决定
+    pub seed: Option<u64>,
+    /// 棋盘的生成方式
+    pub generation: Generation,
+    /// 棋盘的来源
+    #[serde(default)]
+    pub source: BoardSource,
+    /// 每使用一次提示，最终成绩增加的秒数，为0时不惩罚
+    pub hint_penalty: f32,
+    /// 揭开一片空白区域时，离点击的棋子每远一格，覆盖层延迟移除的秒数，为0时立即移除
+    /// 只影响动画，棋盘的状态总是立即更新
+    #[serde(default)]
+    pub reveal_delay: f32,
+    /// 棋子之间的相邻关系，正方形或者六边形
+    #[serde(default)]
+    pub topology: Topology,
+    /// 棋盘的边缘是否相连，相连时每个棋子都有完整的邻居
+    #[serde(default)]
+    pub wrap: bool,
+    /// 棋盘的形状，从文件读取的棋盘使用文件中的形状
+    #[serde(default)]
+    pub shape: BoardShape,
+    /// 每个棋子上最多的炸弹数量，大于1时数字统计周围所有的炸弹，标记会在1到这个数量之间循环
+    #[serde(default = "one_mine_per_tile")]
+    pub mines_per_tile: u8,
+}
+
+fn one_mine_per_tile() -> u8 {
+    1
+}
+
+/// 棋盘配置错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoardConfigError {
+    /// 棋盘的宽度或者高度为0
+    EmptyMap { width: u16, height: u16 },
+    /// 炸弹数量必须小于`limit`
+    TooManyBombs { bomb_count: u16, limit: u32 },
+    /// 棋子之间的间隔为负数
+    NegativePadding(f32),
+    /// 固定的棋子尺寸不是正数
+    InvalidTileSize(f32),
+    /// 自适应棋子尺寸的范围无效
+    InvalidTileSizeRange { min: f32, max: f32 },
+    /// 无需猜测的棋盘至少需要尝试生成一次
+    ZeroAttempts,
+    /// 无法从文件读取棋盘
+    InvalidBoardFile { path: PathBuf, reason: String },
+    /// 揭开动画的延迟为负数
+    NegativeRevealDelay(f32),
+    /// 边缘相连的六边形棋盘高度必须是偶数
+    OddWrappedHexHeight(u16),
+    /// 无法从文件读取棋盘的形状
+    InvalidMask { path: PathBuf, reason: String },
+    /// 每个棋子上的炸弹数量必须在1到`Tile::MAX_MINES`之间
+    InvalidMinesPerTile(u8),
+    /// 一个棋子上有多个炸弹时无法生成无需猜测的棋盘
+    NoGuessMultiMine,
+}
+
+impl Display for BoardConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyMap { width, height } => {
+                write!(
+                    f,
+                    "棋盘尺寸({}, {})无效，宽度和高度都必须大于0",
+                    width, height
+                )
+            }
+            Self::TooManyBombs { bomb_count, limit } => {
+                write!(f, "炸弹数量{}太多，必须小于{}", bomb_count, limit)
+            }
+            Self::NegativePadding(padding) => write!(f, "棋子间隔{}不能为负数", padding),
+            Self::InvalidTileSize(size) => write!(f, "棋子尺寸{}必须大于0", size),
+            Self::InvalidTileSizeRange { min, max } => {
+                write!(
+                    f,
+                    "棋子尺寸范围[{}, {}]无效，最小值必须大于0且不大于最大值",
+                    min, max
+                )
+            }
+            Self::ZeroAttempts => write!(f, "生成无需猜测的棋盘至少需要尝试一次"),
+            Self::InvalidBoardFile { path, reason } => {
+                write!(f, "无法读取棋盘文件{}: {}", path.display(), reason)
+            }
+            Self::NegativeRevealDelay(delay) => write!(f, "揭开动画的延迟{}不能为负数", delay),
+            Self::OddWrappedHexHeight(height) => {
+                write!(f, "边缘相连的六边形棋盘高度{}必须是偶数", height)
+            }
+            Self::InvalidMask { path, reason } => {
+                write!(f, "无法读取棋盘形状{}: {}", path.display(), reason)
+            }
+            Self::InvalidMinesPerTile(count) => write!(
+                f,
+                "每个棋子上的炸弹数量{}无效，必须在1到{}之间",
+                count,
+                Tile::MAX_MINES
+            ),
+            Self::NoGuessMultiMine => write!(f, "一个棋子上有多个炸弹时无法生成无需猜测的棋盘"),
+        }
+    }
+}
+
+impl Error for BoardConfigError {}
+
+impl From<GameError> for BoardConfigError {
+    fn from(error: GameError) -> Self {
+        match error {
+            GameError::EmptyMap { width, height } => Self::EmptyMap { width, height },
+            GameError::TooManyMines { bomb_count, limit } => {
+                Self::TooManyBombs { bomb_count, limit }
+            }
+        }
+    }
+}
+
+impl From<Difficulty> for BoardOptions {
+    /// 使用难度对应的棋盘大小和炸弹数量，其他配置使用默认值
+    fn from(difficulty: Difficulty) -> Self {
+        Self {
+            map_size: difficulty.map_size(),
+            bomb_count: difficulty.bomb_count(),
+            ..Default::default()
+        }
+    }
+}
+
+impl BoardOptions {
+    /// 第一次翻开的棋子及其周围的棋子不会有炸弹
+    pub const SAFE_AREA: u32 = Game::SAFE_AREA as u32;
+
+    /// 自定义棋盘，炸弹数量必须小于棋子数量减去第一次翻开的安全区域，再乘以每个棋子上最多的炸弹数量
+    pub fn custom(
+        width: u16,
+        height: u16,
+        bomb_count: u16,
+        mines_per_tile: u8,
+    ) -> Result<Self, BoardConfigError> {
+        let options = Self {
+            map_size: (width, height),
+            bomb_count,
+            mines_per_tile,
+            ..Default::default()
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// 检查配置是否有效，无效的配置会导致生成棋盘时死循环或者溢出
+    /// 从文件读取的棋盘以文件为准，文件中的炸弹可以比随机生成时更密集，由`TileMap`检查
+    /// 有遮罩的棋盘尺寸以遮罩为准，尺寸和炸弹数量在读取遮罩之后由`Game::validate`检查
+    /// 存档和录像中的配置保存的是读取之后的尺寸和炸弹数量，同样不检查
+    pub fn validate(&self) -> Result<(), BoardConfigError> {
+        if !(1..=Tile::MAX_MINES).contains(&self.mines_per_tile) {
+            return Err(BoardConfigError::InvalidMinesPerTile(self.mines_per_tile));
+        }
+        if self.tile_padding.is_nan() || self.tile_padding < 0.0 {
+            return Err(BoardConfigError::NegativePadding(self.tile_padding));
+        }
+        match self.tile_size {
+            TileSize::Fixed(size) if size.is_nan() || size <= 0.0 => {
+                return Err(BoardConfigError::InvalidTileSize(size));
+            }
+            TileSize::Adaptive { min, max } if min.is_nan() || min <= 0.0 || min > max => {
+                return Err(BoardConfigError::InvalidTileSizeRange { min, max });
+            }
+            _ => (),
+        }
+        match self.generation {
+            Generation::NoGuess { max_attempts: 0 } => return Err(BoardConfigError::ZeroAttempts),
+            Generation::NoGuess { .. } if self.mines_per_tile > 1 => {
+                return Err(BoardConfigError::NoGuessMultiMine);
+            }
+            _ => (),
+        }
+        if self.reveal_delay.is_nan() || self.reveal_delay < 0.0 {
+            return Err(BoardConfigError::NegativeRevealDelay(self.reveal_delay));
+        }
+        if self.has_fixed_size() {
+            self.validate_size()?;
+        }
+        Ok(())
+    }
+
+    /// 棋盘尺寸由`map_size`决定，而不是由棋盘文件或者遮罩决定
+    fn has_fixed_size(&self) -> bool {
+        matches!(self.source, BoardSource::Random) && matches!(self.shape, BoardShape::Rectangle)
+    }
+
+    /// 检查完整的矩形棋盘的尺寸和炸弹数量
+    fn validate_size(&self) -> Result<(), BoardConfigError> {
+        let (width, height) = self.map_size;
+        if width == 0 || height == 0 {
+            return Err(BoardConfigError::EmptyMap { width, height });
+        }
+        let limit = Self::bomb_limit(width, height, self.mines_per_tile);
+        if self.bomb_count as u32 >= limit {
+            return Err(BoardConfigError::TooManyBombs {
+                bomb_count: self.bomb_count,
+                limit,
+            });
+        }
+        self.validate_wrap()
+    }
+
+    /// 六边形棋盘的奇数行向右错开，高度是奇数时最上面一行和最下面一行无法相连
+    /// 从文件或者遮罩读取的棋盘尺寸在读取之后才知道，需要再检查一次
+    pub(crate) fn validate_wrap(&self) -> Result<(), BoardConfigError> {
+        let height = self.map_size.1;
+        if self.wrap && self.topology == Topology::Hex && height % 2 != 0 {
+            return Err(BoardConfigError::OddWrappedHexHeight(height));
+        }
+        Ok(())
+    }
+
+    /// 每个棋子最多`mines_per_tile`个炸弹时，炸弹数量必须小于这个值
+    pub fn bomb_limit(width: u16, height: u16, mines_per_tile: u8) -> u32 {
+        Game::mine_limit(width as usize * height as usize, mines_per_tile)
+    }
 }
 
 impl Default for TileSize {
@@ -61,6 +312,15 @@ impl Default for BoardOptions {
             tile_size: Default::default(),
             tile_padding: 0.,
             safe_start: false,
+            seed: None,
+            generation: Default::default(),
+            source: Default::default(),
+            hint_penalty: 0.,
+            reveal_delay: 0.,
+            topology: Default::default(),
+            wrap: false,
+            shape: Default::default(),
+            mines_per_tile: 1,
         }
     }
 }
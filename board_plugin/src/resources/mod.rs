@@ -1,9 +1,13 @@
 mod board_assets;
 mod board_options;
+mod replay;
+mod save_game;
+mod stats;
 
 pub mod board;
-pub(crate) mod tile;
-pub(crate) mod tile_map;
 
 pub use board_assets::*;
 pub use board_options::*;
+pub use replay::*;
+pub use save_game::SaveGame;
+pub use stats::*;
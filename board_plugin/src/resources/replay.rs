@@ -0,0 +1,153 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::Coordinates;
+
+use super::BoardOptions;
+
+/// 玩家的一次操作，对应`events.rs`中的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerAction {
+    /// 翻开棋子
+    Trigger(Coordinates),
+    /// 标记或者取消标记棋子
+    Mark(Coordinates),
+    /// 双击已经翻开的数字棋子
+    Chord(Coordinates),
+}
+
+/// 录像中的一次操作，记录发生的帧和秒数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// 从棋盘创建开始的帧数
+    pub frame: u32,
+    /// 从棋盘创建开始的秒数，暂停的时间不计算在内
+    pub time: f32,
+    pub action: PlayerAction,
+}
+
+/// 一局游戏的录像，配置中包含了生成棋盘的种子，用相同的操作可以复现整局游戏
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub options: BoardOptions,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        let replay: Self = serde_json::from_str(&data)?;
+        if replay.options.seed.is_none() || replay.options.validate().is_err() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "录像已损坏"));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let data = serde_json::to_string(self)?;
+        fs::write(path, data)
+    }
+}
+
+/// 正在录制的录像，创建新的棋盘时开始录制
+#[derive(Debug, Clone)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+    frame: u32,
+    time: f32,
+}
+
+impl ReplayRecorder {
+    pub fn new(options: BoardOptions) -> Self {
+        Self {
+            replay: Replay {
+                options,
+                events: Vec::new(),
+            },
+            frame: 0,
+            time: 0.,
+        }
+    }
+
+    /// 进入下一帧
+    pub fn tick(&mut self, delta: f32) {
+        self.frame += 1;
+        self.time += delta;
+    }
+
+    pub fn record(&mut self, action: PlayerAction) {
+        self.replay.events.push(ReplayEvent {
+            frame: self.frame,
+            time: self.time,
+            action,
+        });
+    }
+}
+
+/// 回放的速度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackMode {
+    /// 按原来的速度乘以倍数回放
+    Speed(f32),
+    /// 每次按键回放一个操作
+    Step,
+}
+
+/// 回放录像，插入这个资源后，玩家的输入会被忽略，操作来自录像
+#[derive(Debug, Clone)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub mode: PlaybackMode,
+    time: f32,
+    next: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            mode: PlaybackMode::Speed(1.0),
+            time: 0.,
+            next: 0,
+        }
+    }
+
+    /// 从头开始回放
+    pub fn rewind(&mut self) {
+        self.time = 0.;
+        self.next = 0;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.replay.events.len()
+    }
+
+    /// 按照回放速度推进时间
+    pub fn tick(&mut self, delta: f32) {
+        if let PlaybackMode::Speed(speed) = self.mode {
+            self.time += delta * speed;
+        }
+    }
+
+    /// 取出所有到时间的操作，`step`为true时忽略时间，取出录像中下一帧的所有操作
+    /// 同一帧录制的操作总是在同一帧回放，和录制时的结果一致
+    pub fn due_actions(&mut self, step: bool) -> Vec<PlayerAction> {
+        let events = &self.replay.events[self.next..];
+        let count = match events.first() {
+            Some(first) if step => events.iter().take_while(|e| e.frame == first.frame).count(),
+            Some(_) if self.mode != PlaybackMode::Step => {
+                events.iter().take_while(|e| e.time <= self.time).count()
+            }
+            _ => 0,
+        };
+        let due = &events[..count];
+        // 单步回放后，从这个操作的时间继续
+        if let Some(last) = due.last() {
+            self.time = self.time.max(last.time);
+        }
+        let actions = due.iter().map(|e| e.action).collect();
+        self.next += count;
+        actions
+    }
+}
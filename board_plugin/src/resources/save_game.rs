@@ -0,0 +1,57 @@
+use std::{fs, io, path::Path};
+
+use minesweeper_core::Game;
+use serde::{Deserialize, Serialize};
+
+use super::{board::Board, BoardOptions, Clicks};
+
+/// 保存的游戏进度，插入这个资源后，下一次创建棋盘时会从这里恢复，而不是生成新的棋盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    /// 生成棋盘时使用的配置
+    pub options: BoardOptions,
+    /// 棋盘、没有揭开和被标记的棋子以及随机数种子
+    pub game: Game,
+    /// 游戏进行的秒数
+    pub elapsed: f32,
+    /// 使用提示的次数
+    pub hints_used: u32,
+    /// 玩家的点击次数，旧的存档中没有这一项
+    #[serde(default)]
+    pub clicks: Clicks,
+    /// 是否放置过旗子，旧的存档中没有这一项
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+impl SaveGame {
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            options: board.options.clone(),
+            game: board.game.clone(),
+            elapsed: board.elapsed,
+            hints_used: board.hints_used,
+            clicks: board.clicks,
+            flagged: board.flagged,
+        }
+    }
+
+    /// 读取存档，存档损坏或者和配置不一致时返回`InvalidData`错误
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        let save: Self = serde_json::from_str(&data)?;
+        let tile_map = save.game.tile_map();
+        if save.options.validate().is_err()
+            || !save.game.is_valid()
+            || save.options.map_size != (tile_map.width(), tile_map.height())
+        {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "存档已损坏"));
+        }
+        Ok(save)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let data = serde_json::to_string(self)?;
+        fs::write(path, data)
+    }
+}
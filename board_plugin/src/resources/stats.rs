@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// 玩家在棋盘上的点击次数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Clicks {
+    /// 左键，翻开棋子
+    pub left: u32,
+    /// 右键，标记或者取消标记
+    pub right: u32,
+    /// 双击
+    pub chord: u32,
+}
+
+impl Clicks {
+    pub fn total(&self) -> u32 {
+        self.left + self.right + self.chord
+    }
+}
+
+/// 一局游戏结束时的统计，同时作为资源和事件发布
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameStats {
+    /// 是否胜利
+    pub won: bool,
+    /// 用时，包括使用提示增加的秒数
+    pub time: f32,
+    /// 棋盘的3BV
+    pub bbbv: u32,
+    /// 已经完成的3BV，胜利时等于`bbbv`
+    pub solved_bbbv: u32,
+    /// 每秒完成的3BV
+    pub bbbv_per_second: f32,
+    pub clicks: Clicks,
+    /// 效率（IOE），完成的3BV除以点击次数
+    pub efficiency: f32,
+    /// 整局游戏没有放置过任何旗子
+    pub flagless: bool,
+}
+
+impl GameStats {
+    /// `flagged`：是否放置过旗子，点击已经揭开的棋子不算
+    pub fn new(
+        won: bool,
+        time: f32,
+        bbbv: u32,
+        solved_bbbv: u32,
+        clicks: Clicks,
+        flagged: bool,
+    ) -> Self {
+        let ratio = |value: u32, total: f32| {
+            if total > 0. {
+                value as f32 / total
+            } else {
+                0.
+            }
+        };
+        Self {
+            won,
+            time,
+            bbbv,
+            solved_bbbv,
+            bbbv_per_second: ratio(solved_bbbv, time),
+            clicks,
+            efficiency: ratio(solved_bbbv, clicks.total() as f32),
+            flagless: !flagged,
+        }
+    }
+}
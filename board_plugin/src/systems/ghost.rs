@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use minesweeper_core::Game;
+
+use crate::{
+    components::{Coordinates, Ghost},
+    resources::{board::Board, BoardAssets, SpriteMaterial},
+};
+
+/// 副本是半透明的，和棋盘内的棋子区分开
+const GHOST_ALPHA: f32 = 0.3;
+
+/// 副本跟随被复制的棋子显示为覆盖、标记或者揭开的样子
+pub fn update_ghosts(
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut ghosts: Query<(&Ghost, &mut Sprite, &mut Handle<Image>)>,
+) {
+    let board = match board {
+        Some(b) if b.is_changed() => b,
+        _ => return,
+    };
+    for (ghost, mut sprite, mut texture) in ghosts.iter_mut() {
+        let material = ghost_material(&board.game, &board_assets, ghost.0);
+        sprite.color = ghost_color(material);
+        *texture = material.texture.clone();
+    }
+}
+
+/// 被复制的棋子现在的样子
+pub fn ghost_material<'a>(
+    game: &Game,
+    board_assets: &'a BoardAssets,
+    coords: Coordinates,
+) -> &'a SpriteMaterial {
+    if game.is_marked_at(coords) {
+        &board_assets.flag_material
+    } else if game.is_covered_at(coords) {
+        &board_assets.covered_tile_material
+    } else if game.is_bomb_at(coords) {
+        &board_assets.bomb_material
+    } else {
+        &board_assets.tile_material
+    }
+}
+
+pub fn ghost_color(material: &SpriteMaterial) -> Color {
+    let mut color = material.color;
+    color.set_a(GHOST_ALPHA);
+    color
+}
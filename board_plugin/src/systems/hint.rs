@@ -0,0 +1,70 @@
+use bevy::{log, prelude::*};
+
+use crate::{
+    components::Hint,
+    events::{HintRequestedEvent, HintShownEvent},
+    resources::{board::Board, BoardAssets},
+};
+
+pub fn hint_handler(
+    mut commands: Commands,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut hint_requested_evr: EventReader<HintRequestedEvent>,
+    mut hint_shown_ewr: EventWriter<HintShownEvent>,
+    hints: Query<Entity, With<Hint>>,
+) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    // 同一帧内多次请求只提示一次
+    if hint_requested_evr.iter().count() == 0 {
+        return;
+    }
+
+    let (coordinates, probability) = match board.hint() {
+        Some(v) => v,
+        None => {
+            log::info!("没有可以提示的棋子");
+            return;
+        }
+    };
+    let entity = match board.covered_tiles.get(&coordinates) {
+        Some(e) => *e,
+        None => return,
+    };
+
+    // 移除之前的提示
+    for hint in hints.iter() {
+        commands.entity(hint).despawn_recursive();
+    }
+
+    // 提示显示在覆盖的棋子上，棋子被揭开的时候会一起销毁
+    commands.entity(entity).with_children(|parent| {
+        parent
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(board.tile_size - board.options.tile_padding)),
+                    color: board_assets.hint_material.color,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                texture: board_assets.hint_material.texture.clone(),
+                ..Default::default()
+            })
+            .insert(Name::new("Hint"))
+            .insert(Hint);
+    });
+
+    board.hints_used += 1;
+    log::info!(
+        "提示坐标{}的棋子，是炸弹的概率: {:.2}",
+        coordinates,
+        probability
+    );
+    hint_shown_ewr.send(HintShownEvent {
+        coordinates,
+        probability,
+    });
+}
@@ -5,18 +5,43 @@ use bevy::{
 };
 
 use crate::{
-    events::{TileMarkEvent, TileTriggerEvent},
-    resources::board::Board,
+    events::{HintRequestedEvent, TileChordEvent, TileMarkEvent, TileTriggerEvent},
+    resources::{board::Board, PlayerAction, ReplayPlayer, ReplayRecorder},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn input_handling(
     windows: Res<Windows>,
-    board: Res<Board>,
+    board: Option<ResMut<Board>>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
     mut button_evr: EventReader<MouseButtonInput>,
     mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
     mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+    mut hint_requested_ewr: EventWriter<HintRequestedEvent>,
+    player: Option<Res<ReplayPlayer>>,
+    mut recorder: Option<ResMut<ReplayRecorder>>,
 ) {
-    let window = windows.get_primary().unwrap();
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+
+    if keys.just_pressed(KeyCode::H) {
+        log::info!("请求提示");
+        hint_requested_ewr.send(HintRequestedEvent);
+    }
+
+    // 回放录像的时候，棋盘上的操作都来自录像
+    if player.is_some() {
+        return;
+    }
+    // 没有窗口时（比如测试中），只能通过事件操作棋盘
+    let window = match windows.get_primary() {
+        Some(w) => w,
+        None => return,
+    };
 
     for event in button_evr.iter() {
         if let ElementState::Pressed = event.state {
@@ -32,6 +57,31 @@ pub fn input_handling(
                 continue;
             }
             let coordinates = tile_coordinates.unwrap();
+
+            // 中键，或者左右键同时按下，双击已经翻开的棋子
+            let chord = match event.button {
+                MouseButton::Middle => true,
+                MouseButton::Left => buttons.pressed(MouseButton::Right),
+                MouseButton::Right => buttons.pressed(MouseButton::Left),
+                _ => false,
+            };
+            // 所有棋盘上的点击都计入统计，包括没有效果的点击
+            if chord {
+                board.clicks.chord += 1;
+                if !board.is_covered_at(coordinates) {
+                    log::info!("双击坐标{}的棋子", coordinates);
+                    tile_chord_ewr.send(TileChordEvent(coordinates));
+                    record(&mut recorder, PlayerAction::Chord(coordinates));
+                }
+                continue;
+            }
+
+            match event.button {
+                MouseButton::Left => board.clicks.left += 1,
+                MouseButton::Right => board.clicks.right += 1,
+                _ => (),
+            }
+
             // 棋子已经被翻开，则不能进行任何处理
             if !board.is_covered_at(coordinates) {
                 continue;
@@ -43,6 +93,7 @@ pub fn input_handling(
                         log::info!("翻开坐标{}的棋子", coordinates);
                         // 发送事件
                         tile_trigger_ewr.send(TileTriggerEvent(coordinates));
+                        record(&mut recorder, PlayerAction::Trigger(coordinates));
                     }
                 }
                 MouseButton::Right => {
@@ -52,9 +103,17 @@ pub fn input_handling(
                         log::info!("标记坐标{}的棋子", coordinates);
                     }
                     tile_mark_ewr.send(TileMarkEvent(coordinates));
+                    record(&mut recorder, PlayerAction::Mark(coordinates));
                 }
                 _ => (),
             }
         }
     }
 }
+
+/// 按照输入的顺序录制操作，同一帧里不同种类的操作也不会打乱
+fn record(recorder: &mut Option<ResMut<ReplayRecorder>>, action: PlayerAction) {
+    if let Some(recorder) = recorder {
+        recorder.record(action);
+    }
+}
@@ -1,46 +1,79 @@
 use bevy::{log, prelude::*};
 
 use crate::{
+    components::Flag,
     events::TileMarkEvent,
     resources::{board::Board, BoardAssets},
 };
 
 pub fn mark_tiles(
     mut commands: Commands,
-    mut board: ResMut<Board>,
+    board: Option<ResMut<Board>>,
     board_assets: Res<BoardAssets>,
     mut tile_mark_event_rdr: EventReader<TileMarkEvent>,
     query: Query<&Children>,
+    flags: Query<Entity, With<Flag>>,
 ) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
     for event in tile_mark_event_rdr.iter() {
-        if let Some((entity, mark)) = board.try_toggle_mark(&event.0) {
-            if mark {
-                commands.entity(entity).with_children(|parent| {
-                    parent
-                        .spawn_bundle(SpriteBundle {
-                            sprite: Sprite {
-                                custom_size: Some(Vec2::splat(board.tile_size)),
-                                color: board_assets.flag_material.color,
-                                ..Default::default()
-                            },
-                            transform: Transform::from_xyz(0.0, 0.0, 1.0),
-                            texture: board_assets.flag_material.texture.clone(),
-                            ..Default::default()
-                        })
-                        .insert(Name::new("Flag"));
-                });
-            } else {
-                let children = match query.get(entity) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        log::error!("获取标记组件实体失败: {}", e);
-                        continue;
-                    }
-                };
-                for child in children.iter() {
-                    commands.entity(*child).despawn_recursive();
-                }
+        if let Some((entity, count)) = board.toggle_mark(&event.0) {
+            // 第一个旗子直接显示，旗子的数量变化或者取消标记时先移除原来的标记
+            if count != 1 {
+                remove_flag(&mut commands, entity, &query, &flags);
             }
+            if count > 0 {
+                spawn_flag(&mut commands, entity, board.tile_size, count, &board_assets);
+            }
+        }
+    }
+}
+
+/// 移除覆盖层`entity`上的标记，提示等其他子实体保留
+pub fn remove_flag(
+    commands: &mut Commands,
+    entity: Entity,
+    query: &Query<&Children>,
+    flags: &Query<Entity, With<Flag>>,
+) {
+    let children = match query.get(entity) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("获取标记组件实体失败: {}", e);
+            return;
         }
+    };
+    for child in children.iter().filter(|c| flags.get(**c).is_ok()) {
+        commands.entity(*child).despawn_recursive();
     }
 }
+
+/// 在覆盖层`entity`上显示一个标记，有多个旗子时同时显示旗子的数量
+pub fn spawn_flag(
+    commands: &mut Commands,
+    entity: Entity,
+    size: f32,
+    count: u8,
+    board_assets: &BoardAssets,
+) {
+    commands.entity(entity).with_children(|parent| {
+        let mut flag = parent.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(size)),
+                color: board_assets.flag_material.color,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            texture: board_assets.flag_material.texture.clone(),
+            ..Default::default()
+        });
+        flag.insert(Name::new("Flag")).insert(Flag);
+        if count > 1 {
+            flag.with_children(|parent| {
+                parent.spawn_bundle(board_assets.count_text_bundle(count, size / 2.0));
+            });
+        }
+    });
+}
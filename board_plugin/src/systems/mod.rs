@@ -1,3 +1,9 @@
+pub mod ghost;
+pub mod hint;
 pub mod input;
 pub mod mark;
+pub mod outcome;
+pub mod replay;
+pub mod stats;
+pub mod timer;
 pub mod uncover;
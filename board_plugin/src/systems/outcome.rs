@@ -0,0 +1,71 @@
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+use bevy::{log, prelude::*};
+
+use crate::{
+    components::Coordinates,
+    events::BombExplosionEvent,
+    resources::{
+        board::{Board, GameOutcome},
+        BoardAssets,
+    },
+};
+
+/// 失败：高亮爆炸的棋子，标出错误的标记，并揭开剩下的炸弹
+pub fn game_lost(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut tiles: Query<(&Coordinates, &mut Sprite)>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    if bomb_explosion_evr.iter().count() == 0 {
+        return;
+    }
+    let exploded = match board.game.outcome() {
+        GameOutcome::Lost(coords) => coords,
+        _ => return,
+    };
+    log::info!("游戏失败，爆炸的坐标: {}", exploded);
+
+    for (coords, mut sprite) in tiles.iter_mut() {
+        if *coords == exploded {
+            sprite.color = board_assets.exploded_material.color;
+        }
+    }
+
+    let size = board.tile_size - board.options.tile_padding;
+    for (coords, entity) in board.covered_tiles.iter() {
+        let bomb = board.is_bomb_at(*coords);
+        let marked = board.is_marked_at(*coords);
+        // 旗子的数量和炸弹的数量不一致也是错误的标记
+        let wrong = board.game.mark_count_at(*coords) != board.game.tile_map().mines_at(*coords);
+        if bomb && !marked {
+            // 只是展示炸弹的位置，游戏已经结束，不需要再经过游戏规则
+            commands.entity(*entity).despawn_recursive();
+        } else if marked && wrong {
+            // 在旗子上画一个叉：两条沿着对角线的线段
+            commands.entity(*entity).with_children(|parent| {
+                for angle in [FRAC_PI_4, -FRAC_PI_4] {
+                    parent
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(Vec2::new(size * SQRT_2 * 0.8, size / 8.0)),
+                                color: board_assets.wrong_flag_material.color,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(0.0, 0.0, 2.0)
+                                .with_rotation(Quat::from_rotation_z(angle)),
+                            texture: board_assets.wrong_flag_material.texture.clone(),
+                            ..Default::default()
+                        })
+                        .insert(Name::new("Wrong Flag"));
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::{log, prelude::*};
+
+use crate::{
+    events::{TileChordEvent, TileMarkEvent, TileTriggerEvent},
+    resources::{PlaybackMode, PlayerAction, ReplayPlayer, ReplayRecorder},
+};
+
+/// 录像进入下一帧，玩家的操作由`input_handling`按照输入的顺序录制
+pub fn tick_recorder(
+    time: Res<Time>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    player: Option<Res<ReplayPlayer>>,
+) {
+    // 回放中的操作来自录像，不需要再次录制
+    if let (Some(mut recorder), None) = (recorder, player) {
+        recorder.tick(time.delta_seconds());
+    }
+}
+
+/// 回放录像中的操作
+/// 数字键1、2、4切换回放速度，S切换单步回放，N回放下一帧的操作
+pub fn play_actions(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    player: Option<ResMut<ReplayPlayer>>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
+    mut tile_mark_ewr: EventWriter<TileMarkEvent>,
+    mut tile_chord_ewr: EventWriter<TileChordEvent>,
+) {
+    let mut player = match player {
+        Some(p) => p,
+        None => return,
+    };
+    let mut step = false;
+    for key in keys.get_just_pressed() {
+        let mode = match key {
+            KeyCode::Key1 => PlaybackMode::Speed(1.0),
+            KeyCode::Key2 => PlaybackMode::Speed(2.0),
+            KeyCode::Key4 => PlaybackMode::Speed(4.0),
+            KeyCode::S if player.mode == PlaybackMode::Step => PlaybackMode::Speed(1.0),
+            KeyCode::S => PlaybackMode::Step,
+            KeyCode::N => {
+                step = true;
+                continue;
+            }
+            _ => continue,
+        };
+        log::info!("回放模式: {:?}", mode);
+        player.mode = mode;
+    }
+    player.tick(time.delta_seconds());
+
+    let actions = player.due_actions(step);
+    if actions.is_empty() {
+        return;
+    }
+    for action in actions {
+        match action {
+            PlayerAction::Trigger(coords) => tile_trigger_ewr.send(TileTriggerEvent(coords)),
+            PlayerAction::Mark(coords) => tile_mark_ewr.send(TileMarkEvent(coords)),
+            PlayerAction::Chord(coords) => tile_chord_ewr.send(TileChordEvent(coords)),
+        }
+    }
+    if player.is_finished() {
+        log::info!("回放结束");
+    }
+}
+
+/// 重新开始时从头回放
+pub fn rewind_replay(player: Option<ResMut<ReplayPlayer>>) {
+    if let Some(mut player) = player {
+        player.rewind();
+    }
+}
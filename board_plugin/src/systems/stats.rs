@@ -0,0 +1,48 @@
+use bevy::{log, prelude::*};
+
+use crate::{
+    events::{BoardCompletedEvent, BombExplosionEvent},
+    resources::{board::Board, GameStats},
+};
+
+/// 游戏结束时统计这局游戏，插入`GameStats`资源并发送同样的事件
+pub fn publish_stats(
+    mut commands: Commands,
+    board: Option<Res<Board>>,
+    mut board_completed_evr: EventReader<BoardCompletedEvent>,
+    mut bomb_explosion_evr: EventReader<BombExplosionEvent>,
+    mut game_stats_ewr: EventWriter<GameStats>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    let won = board_completed_evr.iter().count() > 0;
+    let lost = bomb_explosion_evr.iter().count() > 0;
+    if !won && !lost {
+        return;
+    }
+
+    let stats = GameStats::new(
+        won,
+        board.elapsed + board.hint_penalty(),
+        board.bbbv,
+        board.game.solved_bbbv(),
+        board.clicks,
+        board.flagged,
+    );
+    log::info!(
+        "用时: {:.2}s，3BV: {}/{}，3BV/s: {:.2}，效率: {:.0}%，点击: {}/{}/{}{}",
+        stats.time,
+        stats.solved_bbbv,
+        stats.bbbv,
+        stats.bbbv_per_second,
+        stats.efficiency * 100.,
+        stats.clicks.left,
+        stats.clicks.right,
+        stats.clicks.chord,
+        if stats.flagless { "，无标记" } else { "" }
+    );
+    commands.insert_resource(stats.clone());
+    game_stats_ewr.send(stats);
+}
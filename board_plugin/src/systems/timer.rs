@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+use crate::resources::board::Board;
+
+/// 从玩家第一次翻开棋子开始计时，暂停或者游戏结束时停止
+pub fn tick_timer(time: Res<Time>, board: Option<ResMut<Board>>) {
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    if board.started && board.is_playing() {
+        board.elapsed += time.delta_seconds();
+    }
+}
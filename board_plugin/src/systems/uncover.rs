@@ -1,69 +1,117 @@
 use bevy::{log, prelude::*};
 
+use minesweeper_core::RevealOutcome;
+
 use crate::{
-    components::{Bomb, BombNeighbor, Coordinates, Uncover},
-    events::{BoardCompletedEvent, BombExplosionEvent, TileTriggerEvent},
-    resources::board::Board,
+    components::{Coordinates, Flag, Uncover},
+    events::{BoardCompletedEvent, BombExplosionEvent, TileChordEvent, TileTriggerEvent},
+    resources::{board::Board, BoardAssets},
+    systems::mark::{remove_flag, spawn_flag},
 };
 
-pub fn trigger_event_handler(
-    mut commands: Commands,
-    board: Res<Board>,
-    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
+/// 双击会翻开周围所有没有标记的棋子，如果标记错了，就会翻开炸弹
+/// 每个翻开的棋子都发送和单击一样的`TileTriggerEvent`，其他系统不需要区分单击和双击
+pub fn chord_handler(
+    board: Option<Res<Board>>,
+    mut tile_chord_evr: EventReader<TileChordEvent>,
+    mut tile_trigger_ewr: EventWriter<TileTriggerEvent>,
 ) {
-    for trigger_event in tile_trigger_evr.iter() {
-        if board.is_bomb_at(trigger_event.0) {
-            log::error!("这是一个炸弹，游戏结束！");
-            for (_, entity) in board.covered_tiles.iter() {
-                commands.entity(*entity).insert(Uncover);
-            }
-            break;
-        }
-
-        if let Some(entity) = board.tile_to_uncover(&trigger_event.0) {
-            commands.entity(*entity).insert(Uncover);
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for event in tile_chord_evr.iter() {
+        for coords in board.chord_tiles(event.0) {
+            tile_trigger_ewr.send(TileTriggerEvent(coords));
         }
     }
 }
 
-pub fn uncover_tiles(
+/// 揭开玩家点击或者双击的棋子，空白区域在这一帧全部揭开，棋盘的状态不会落后于输入
+/// 覆盖层按照`BoardOptions::reveal_delay`由近到远依次移除
+#[allow(clippy::too_many_arguments)]
+pub fn trigger_event_handler(
     mut commands: Commands,
-    mut board: ResMut<Board>,
-    children: Query<(Entity, &Parent), With<Uncover>>,
-    parents: Query<(&Coordinates, Option<&Bomb>, Option<&BombNeighbor>)>,
+    board: Option<ResMut<Board>>,
+    board_assets: Res<BoardAssets>,
+    mut tile_trigger_evr: EventReader<TileTriggerEvent>,
     mut board_completed_event_wr: EventWriter<BoardCompletedEvent>,
     mut bomb_explosion_event_wr: EventWriter<BombExplosionEvent>,
+    children: Query<&Children>,
+    flags: Query<Entity, With<Flag>>,
 ) {
-    for (entity, parent) in children.iter() {
-        // 销毁覆盖在棋子上的组件
-        commands.entity(entity).despawn_recursive();
+    let mut board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    // 游戏结束后不再揭开任何棋子
+    if !board.is_playing() {
+        return;
+    }
 
-        let (coords, bomb, bomb_counter) = match parents.get(parent.0) {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!("{}", e);
-                continue;
+    let pending: Vec<Coordinates> = tile_trigger_evr.iter().map(|e| e.0).collect();
+    for coords in pending {
+        let (outcome, covers) = board.reveal(coords);
+        // 开启`safe_start`时，中心的棋子在创建棋盘时就揭开了，计时器从玩家的操作开始
+        if outcome != RevealOutcome::Ignored {
+            board.started = true;
+        }
+        for (revealed, entity) in covers {
+            // 离点击的棋子越远，覆盖层移除得越晚
+            let distance = board.game.tile_map().distance(coords, revealed);
+            let delay = board.options.reveal_delay * distance as f32;
+            if delay > 0. {
+                commands.entity(entity).insert(Uncover { delay });
+            } else {
+                commands.entity(entity).despawn_recursive();
             }
-        };
-
-        match board.try_uncover_tile(coords) {
-            None => log::debug!("试图揭开一个已经被揭开的棋子"),
-            Some(e) => log::debug!("揭开的棋子 {} (entity: {:?})", coords, e),
         }
 
-        if board.is_completed() {
-            board_completed_event_wr.send(BoardCompletedEvent);
+        match outcome {
+            RevealOutcome::Ignored => log::debug!("试图揭开一个已经被揭开的棋子"),
+            RevealOutcome::Revealed { revealed } => {
+                log::debug!("揭开了{}个棋子", revealed.len());
+            }
+            RevealOutcome::Exploded => {
+                log::info!("Boom!");
+                bomb_explosion_event_wr.send(BombExplosionEvent);
+                break;
+            }
+            RevealOutcome::Won { flagged, .. } => {
+                log::info!("游戏胜利！");
+                // 胜利时，剩下的隐藏棋子都是炸弹，自动标记出来，旗子数量不对的重新标记
+                for coords in flagged {
+                    if let Some(entity) = board.covered_tiles.get(&coords) {
+                        if children.get(*entity).is_ok() {
+                            remove_flag(&mut commands, *entity, &children, &flags);
+                        }
+                        let count = board.game.mark_count_at(coords);
+                        spawn_flag(
+                            &mut commands,
+                            *entity,
+                            board.tile_size,
+                            count,
+                            &board_assets,
+                        );
+                    }
+                }
+                board_completed_event_wr.send(BoardCompletedEvent);
+                break;
+            }
         }
+    }
+}
 
-        // 如果揭开的是炸弹棋子，则结束游戏
-        if bomb.is_some() {
-            log::info!("Boom!");
-            bomb_explosion_event_wr.send(BombExplosionEvent);
-        } else if bomb_counter.is_none() {
-            // 如果相邻的棋子是空的(Empty)，则添加到commands中，等待下一帧刷新的时候，揭开这些棋子
-            for entity in board.adjacent_covered_tiles(*coords) {
-                commands.entity(entity).insert(Uncover);
-            }
+/// 延迟时间到了以后移除覆盖层，只影响画面
+pub fn uncover_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut covers: Query<(Entity, &mut Uncover)>,
+) {
+    for (entity, mut uncover) in covers.iter_mut() {
+        uncover.delay -= time.delta_seconds();
+        if uncover.delay <= 0. {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
@@ -0,0 +1,223 @@
+//! 不需要窗口的测试环境：使用`MinimalPlugins`运行`BoardPlugin`，通过事件操作棋盘
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use tempfile::TempDir;
+
+use bevy::{app::Events, prelude::*, window::WindowPlugin};
+use board_plugin::{
+    components::{Coordinates, Ghost, Uncover},
+    events::{
+        BoardCompletedEvent, BombExplosionEvent, HintRequestedEvent, HintShownEvent,
+        TileChordEvent, TileMarkEvent, TileTriggerEvent,
+    },
+    resources::{
+        board::Board, BoardAssets, BoardOptions, BoardPosition, BoardSource, SpriteMaterial,
+        TileSize,
+    },
+    BoardPlugin,
+};
+
+/// 中间一列炸弹把棋盘分成左右两块空白区域
+pub const WALL: &str = "
+..*.....
+..*.....
+..*.....
+..*.....
+";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TestState {
+    InGame,
+    Over,
+}
+
+pub struct TestBoard {
+    pub app: App,
+    /// 棋盘文件所在的临时目录，和棋盘一起删除
+    dir: TempDir,
+}
+
+impl TestBoard {
+    /// 使用文本格式的固定棋盘（`*`为炸弹，第一行在最上面），创建棋盘后返回
+    /// `name`是棋盘文件的名字，文件放在测试结束时自动删除的临时目录中
+    pub fn new(name: &str, text: &str) -> Self {
+        Self::with_options(name, text, |_| ())
+    }
+
+    pub fn with_options(name: &str, text: &str, configure: impl FnOnce(&mut BoardOptions)) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let mut options = BoardOptions {
+            tile_size: TileSize::Fixed(10.),
+            position: BoardPosition::Custom(Vec3::ZERO),
+            source: BoardSource::File(write_file(&dir, &format!("{}.txt", name), text)),
+            ..Default::default()
+        };
+        configure(&mut options);
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(bevy::input::InputPlugin)
+            .insert_resource(WindowDescriptor::default())
+            .insert_resource(test_assets())
+            .insert_resource(options)
+            .add_state(TestState::InGame)
+            .add_plugin(BoardPlugin {
+                running_state: TestState::InGame,
+                end_state: TestState::Over,
+            });
+        // 第一帧进入状态，创建棋盘
+        app.update();
+        assert!(app.world.get_resource::<Board>().is_some(), "没有创建棋盘");
+        Self { app, dir }
+    }
+
+    pub fn board(&self) -> &Board {
+        self.app.world.get_resource::<Board>().unwrap()
+    }
+
+    pub fn state(&self) -> &TestState {
+        self.app
+            .world
+            .get_resource::<State<TestState>>()
+            .unwrap()
+            .current()
+    }
+
+    /// 揭开一个棋子，并运行一帧
+    pub fn trigger(&mut self, x: u16, y: u16) {
+        self.send(TileTriggerEvent(Coordinates { x, y }));
+    }
+
+    /// 标记或者取消标记一个棋子，并运行一帧
+    pub fn mark(&mut self, x: u16, y: u16) {
+        self.send(TileMarkEvent(Coordinates { x, y }));
+    }
+
+    /// 双击一个已经揭开的棋子，并运行一帧
+    pub fn chord(&mut self, x: u16, y: u16) {
+        self.send(TileChordEvent(Coordinates { x, y }));
+    }
+
+    /// 请求提示，并运行一帧，返回被提示的棋子
+    pub fn hint(&mut self) -> Option<Coordinates> {
+        self.send(HintRequestedEvent);
+        let events = self
+            .app
+            .world
+            .get_resource::<Events<HintShownEvent>>()
+            .unwrap();
+        events
+            .get_reader()
+            .iter(events)
+            .last()
+            .map(|e| e.coordinates)
+    }
+
+    pub fn send<E: Send + Sync + 'static>(&mut self, event: E) {
+        self.app
+            .world
+            .get_resource_mut::<Events<E>>()
+            .unwrap()
+            .send(event);
+        self.app.update();
+    }
+
+    /// 最近两帧内发出的事件数量
+    pub fn events<E: Send + Sync + 'static>(&self) -> usize {
+        let events = self.app.world.get_resource::<Events<E>>().unwrap();
+        events.get_reader().iter(events).count()
+    }
+
+    pub fn explosions(&self) -> usize {
+        self.events::<BombExplosionEvent>()
+    }
+
+    pub fn completions(&self) -> usize {
+        self.events::<BoardCompletedEvent>()
+    }
+
+    pub fn is_covered(&self, x: u16, y: u16) -> bool {
+        self.board().game.is_covered_at(Coordinates { x, y })
+    }
+
+    pub fn covered_count(&self) -> usize {
+        self.board().game.covered().count()
+    }
+
+    /// 正在等待移除的覆盖层
+    pub fn uncovering(&mut self) -> usize {
+        let mut query = self.app.world.query::<&Uncover>();
+        query.iter(&self.app.world).count()
+    }
+
+    /// 揭开的动画向前推进`seconds`秒，并运行一帧
+    /// 直接减少覆盖层剩余的时间，不需要等待真实的时间
+    pub fn advance(&mut self, seconds: f32) {
+        let mut query = self.app.world.query::<&mut Uncover>();
+        for mut uncover in query.iter_mut(&mut self.app.world) {
+            uncover.delay -= seconds;
+        }
+        self.app.update();
+    }
+
+    /// 还存在的覆盖层，包括正在等待移除的
+    pub fn covers(&mut self) -> usize {
+        self.count_named("Tile Cover")
+    }
+
+    /// 边缘相连的棋盘四周的副本
+    pub fn ghosts(&mut self) -> usize {
+        let mut query = self.app.world.query::<&Ghost>();
+        query.iter(&self.app.world).count()
+    }
+
+    /// 画在棋盘上的标记
+    pub fn flags(&mut self) -> usize {
+        self.count_named("Flag")
+    }
+
+    /// 画在棋盘上的提示
+    pub fn hints(&mut self) -> usize {
+        self.count_named("Hint")
+    }
+
+    /// 失败后画在错误标记上的叉，每个叉由两条线段组成
+    pub fn wrong_flag_strokes(&mut self) -> usize {
+        self.count_named("Wrong Flag")
+    }
+
+    fn count_named(&mut self, name: &str) -> usize {
+        let mut query = self.app.world.query::<&Name>();
+        query
+            .iter(&self.app.world)
+            .filter(|n| n.as_str() == name)
+            .count()
+    }
+}
+
+/// 把文本写入临时目录中的文件，返回文件的路径
+pub fn write_file(dir: &TempDir, name: &str, text: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, text).unwrap();
+    path
+}
+
+/// 测试中不加载任何资源，使用默认的纹理和字体
+fn test_assets() -> BoardAssets {
+    BoardAssets {
+        label: "Test".to_string(),
+        board_material: SpriteMaterial::default(),
+        tile_material: SpriteMaterial::default(),
+        covered_tile_material: SpriteMaterial::default(),
+        bomb_counter_font: Handle::default(),
+        bomb_counter_colors: BoardAssets::default_colors(),
+        flag_material: SpriteMaterial::default(),
+        bomb_material: SpriteMaterial::default(),
+        hint_material: SpriteMaterial::default(),
+        exploded_material: SpriteMaterial::default(),
+        wrong_flag_material: SpriteMaterial::default(),
+    }
+}
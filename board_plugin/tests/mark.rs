@@ -0,0 +1,134 @@
+mod common;
+
+use board_plugin::{
+    components::Coordinates, events::TileTriggerEvent, resources::board::GameOutcome,
+};
+use common::{TestBoard, WALL};
+
+#[test]
+fn marking_toggles_the_flag() {
+    let mut board = TestBoard::new("toggle", WALL);
+    board.mark(2, 0);
+    assert!(board.board().game.is_marked_at(Coordinates { x: 2, y: 0 }));
+    assert_eq!(board.board().game.remaining_mines(), 3);
+    assert_eq!(board.flags(), 1);
+
+    board.mark(2, 0);
+    assert!(!board.board().game.is_marked_at(Coordinates { x: 2, y: 0 }));
+    assert_eq!(board.board().game.remaining_mines(), 4);
+    assert_eq!(board.flags(), 0);
+    // 取消标记之后这一局仍然不是无标记
+    assert!(board.board().flagged);
+}
+
+#[test]
+fn unmarking_keeps_the_hint() {
+    let mut board = TestBoard::new("hint", WALL);
+    board.trigger(0, 0);
+    let hinted = board.hint().unwrap();
+    assert_eq!(board.hints(), 1);
+
+    // 标记和取消标记只移除旗子，提示仍然留在棋子上
+    board.mark(hinted.x, hinted.y);
+    board.mark(hinted.x, hinted.y);
+    assert_eq!(board.flags(), 0);
+    assert_eq!(board.hints(), 1);
+}
+
+#[test]
+fn marked_tiles_are_not_revealed() {
+    let mut board = TestBoard::new("marked", WALL);
+    board.mark(6, 0);
+    board.trigger(6, 0);
+    assert!(board.is_covered(6, 0));
+    assert_eq!(board.covered_count(), 32);
+
+    // 从旁边揭开空白区域时，标记的棋子仍然保持覆盖
+    board.trigger(6, 3);
+    assert!(board.is_covered(6, 0));
+    assert!(!board.is_covered(7, 0));
+    assert_eq!(board.flags(), 1);
+}
+
+#[test]
+fn revealed_tiles_cannot_be_marked() {
+    let mut board = TestBoard::new("revealed", WALL);
+    board.trigger(3, 0);
+    board.mark(3, 0);
+    assert!(!board.board().game.is_marked_at(Coordinates { x: 3, y: 0 }));
+    assert_eq!(board.flags(), 0);
+    assert!(!board.board().flagged);
+}
+
+#[test]
+fn chord_reveals_unmarked_neighbors() {
+    let mut board = TestBoard::new("chord", WALL);
+    board.trigger(3, 0);
+    board.mark(2, 0);
+    board.mark(2, 1);
+    board.chord(3, 0);
+
+    // 每个翻开的棋子都和单击一样发送事件
+    assert_eq!(board.events::<TileTriggerEvent>(), 3);
+    // 周围的空白棋子会继续展开整个区域
+    assert_eq!(board.covered_count(), 12);
+    assert_eq!(board.flags(), 2);
+    assert_eq!(board.explosions(), 0);
+}
+
+#[test]
+fn chord_with_a_wrong_flag_explodes() {
+    let mut board = TestBoard::new("wrong-chord", WALL);
+    board.trigger(3, 0);
+    board.mark(2, 0);
+    board.mark(4, 0);
+    board.chord(3, 0);
+
+    assert_eq!(board.explosions(), 1);
+    assert_eq!(
+        board.board().game.outcome(),
+        GameOutcome::Lost(Coordinates { x: 2, y: 1 })
+    );
+    // 只有(4, 0)的标记是错误的，在它上面画一个叉
+    board.app.update();
+    assert_eq!(board.wrong_flag_strokes(), 2);
+}
+
+/// 左上角的棋子上有3个炸弹，右上角有2个
+const STACKED: &str = "
+3...2
+.....
+.....
+";
+
+#[test]
+fn flags_cycle_on_multi_mine_tiles() {
+    let mut board = TestBoard::new("multi-mine", STACKED);
+    assert_eq!(board.board().options.mines_per_tile, 3);
+    assert_eq!(board.board().game.remaining_mines(), 5);
+
+    for count in 1..=3 {
+        board.mark(0, 2);
+        assert_eq!(
+            board.board().game.mark_count_at(Coordinates { x: 0, y: 2 }),
+            count
+        );
+        assert_eq!(board.flags(), 1);
+    }
+    assert_eq!(board.board().game.remaining_mines(), 2);
+
+    board.mark(0, 2);
+    assert!(!board.board().game.is_marked_at(Coordinates { x: 0, y: 2 }));
+    assert_eq!(board.flags(), 0);
+}
+
+#[test]
+fn winning_corrects_multi_mine_flags() {
+    let mut board = TestBoard::new("multi-mine-win", STACKED);
+    board.mark(0, 2);
+    board.trigger(2, 0);
+
+    assert_eq!(board.completions(), 1);
+    assert_eq!(board.board().game.remaining_mines(), 0);
+    assert_eq!(board.flags(), 2);
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use board_plugin::resources::{BoardConfigError, BoardOptions, BoardShape, BoardSource};
+
+#[test]
+fn rectangles_check_the_mine_limit() {
+    let options = BoardOptions {
+        map_size: (4, 4),
+        bomb_count: 7,
+        ..Default::default()
+    };
+    assert_eq!(
+        options.validate(),
+        Err(BoardConfigError::TooManyBombs {
+            bomb_count: 7,
+            limit: 7
+        })
+    );
+}
+
+#[test]
+fn masks_are_checked_after_they_are_read() {
+    // 遮罩决定棋盘的尺寸，配置中的尺寸和炸弹数量的关系没有意义
+    let options = BoardOptions {
+        map_size: (0, 0),
+        bomb_count: 30,
+        shape: BoardShape::Mask(PathBuf::from("mask.png")),
+        ..Default::default()
+    };
+    assert_eq!(options.validate(), Ok(()));
+}
+
+#[test]
+fn board_files_may_be_denser_than_random_boards() {
+    // 读取之后配置中保存的是文件的尺寸和炸弹数量，存档和录像需要能够再次读取
+    let options = BoardOptions {
+        map_size: (3, 3),
+        bomb_count: 5,
+        source: BoardSource::File(PathBuf::from("board.txt")),
+        ..Default::default()
+    };
+    assert_eq!(options.validate(), Ok(()));
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use board_plugin::{
+    components::Coordinates,
+    resources::{board::GameOutcome, BoardShape, BoardSource, Topology},
+};
+use common::{write_file, TestBoard, TestState, WALL};
+use minesweeper_core::Tile;
+
+/// 只有两个角上有炸弹，揭开任意一个空白棋子就会胜利
+const CORNERS: &str = "
+*...
+....
+....
+...*
+";
+
+#[test]
+fn empty_region_is_revealed_in_one_frame() {
+    let mut board = TestBoard::new("empty-region", WALL);
+    board.trigger(6, 0);
+
+    // 右边的5列全部揭开，左边和炸弹保持覆盖
+    assert_eq!(board.covered_count(), 12);
+    assert!(!board.is_covered(3, 3));
+    assert!(board.is_covered(1, 0));
+    assert_eq!(board.board().covered_tiles.len(), 12);
+    assert_eq!(board.covers(), 12);
+    assert_eq!(board.uncovering(), 0);
+    assert_eq!(board.explosions(), 0);
+    assert_eq!(board.completions(), 0);
+    assert_eq!(board.board().game.outcome(), GameOutcome::Playing);
+}
+
+#[test]
+fn number_reveals_a_single_tile() {
+    let mut board = TestBoard::new("number", WALL);
+    board.trigger(3, 0);
+
+    assert_eq!(board.covered_count(), 31);
+    assert!(!board.is_covered(3, 0));
+    assert!(board.is_covered(4, 0));
+    assert_eq!(board.covers(), 31);
+
+    // 再次揭开同一个棋子不会有任何变化
+    board.trigger(3, 0);
+    assert_eq!(board.covered_count(), 31);
+}
+
+#[test]
+fn revealing_a_bomb_explodes() {
+    let mut board = TestBoard::new("explosion", WALL);
+    board.trigger(2, 1);
+
+    assert_eq!(board.explosions(), 1);
+    assert_eq!(board.completions(), 0);
+    assert_eq!(
+        board.board().game.outcome(),
+        GameOutcome::Lost(Coordinates { x: 2, y: 1 })
+    );
+    board.app.update();
+    assert_eq!(board.state(), &TestState::Over);
+
+    // 游戏结束后不再揭开任何棋子
+    board.trigger(6, 0);
+    assert!(board.is_covered(6, 0));
+}
+
+#[test]
+fn revealing_every_safe_tile_completes_the_board() {
+    let mut board = TestBoard::new("completed", WALL);
+    board.trigger(6, 0);
+    assert_eq!(board.completions(), 0);
+    board.trigger(0, 0);
+
+    assert_eq!(board.completions(), 1);
+    assert_eq!(board.explosions(), 0);
+    assert_eq!(board.board().game.outcome(), GameOutcome::Won);
+    // 剩下的炸弹被自动标记
+    assert_eq!(board.covered_count(), 4);
+    assert_eq!(board.flags(), 4);
+    board.app.update();
+    assert_eq!(board.state(), &TestState::Over);
+}
+
+#[test]
+fn first_reveal_can_complete_the_board() {
+    let mut board = TestBoard::new("first-reveal", CORNERS);
+    board.trigger(0, 0);
+
+    assert_eq!(board.completions(), 1);
+    assert_eq!(board.covered_count(), 2);
+    assert_eq!(board.covers(), 2);
+    assert_eq!(board.flags(), 2);
+}
+
+#[test]
+fn animated_reveal_updates_the_board_first() {
+    let mut board = TestBoard::with_options("animated", WALL, |options| {
+        options.reveal_delay = 0.25;
+    });
+    board.trigger(6, 0);
+
+    // 棋盘的状态立即更新，覆盖层按照距离依次移除，点击的棋子没有延迟
+    assert_eq!(board.covered_count(), 12);
+    assert_eq!(board.board().covered_tiles.len(), 12);
+    assert_eq!(board.uncovering(), 19);
+    assert_eq!(board.covers(), 12 + 19);
+
+    // 每过一段延迟，移除距离远一格的覆盖层
+    board.advance(0.25);
+    assert_eq!(board.uncovering(), 14);
+    board.advance(0.25);
+    assert_eq!(board.uncovering(), 8);
+    board.advance(0.25);
+    assert_eq!(board.uncovering(), 0);
+    assert_eq!(board.covers(), 12);
+}
+
+#[test]
+fn hex_board_uses_six_neighbors() {
+    let mut board = TestBoard::with_options("hex", WALL, |options| {
+        options.topology = Topology::Hex;
+    });
+    board.trigger(6, 0);
+
+    assert_eq!(board.covered_count(), 12);
+    let tile_map = board.board().game.tile_map();
+    assert_eq!(tile_map.topology(), Topology::Hex);
+    // 奇数行向右错开，(3, 1)只和(2, 1)相邻
+    assert_eq!(tile_map[Coordinates { x: 3, y: 1 }], Tile::BombNeighbor(1));
+    assert_eq!(tile_map[Coordinates { x: 3, y: 0 }], Tile::BombNeighbor(2));
+}
+
+#[test]
+fn wrapped_board_reveals_across_the_edges() {
+    let mut board = TestBoard::with_options("wrap", WALL, |options| {
+        options.wrap = true;
+    });
+    // 四周一圈副本
+    assert_eq!(board.ghosts(), 2 * 8 + 2 * 4 + 4);
+
+    // 最左边一列和最右边一列相邻，两块空白区域连在一起
+    board.trigger(6, 0);
+    assert_eq!(board.completions(), 1);
+    assert_eq!(board.covered_count(), 4);
+    let tile_map = board.board().game.tile_map();
+    assert_eq!(tile_map[Coordinates { x: 0, y: 0 }], Tile::Empty);
+    assert_eq!(tile_map[Coordinates { x: 1, y: 0 }], Tile::BombNeighbor(3));
+}
+
+/// 中间有一个空洞的环
+const RING: &str = "
+..*..
+.###.
+.###.
+.###.
+.....
+";
+
+#[test]
+fn void_cells_are_not_spawned() {
+    let mut board = TestBoard::new("void", RING);
+    assert_eq!(board.covers(), 16);
+    assert_eq!(board.board().covered_tiles.len(), 16);
+
+    // 点击空洞什么也不会发生
+    board.trigger(2, 2);
+    assert_eq!(board.covered_count(), 16);
+
+    // 空洞不是邻居，空白区域绕过空洞展开，揭开所有安全的棋子
+    board.trigger(2, 0);
+    assert_eq!(board.completions(), 1);
+    assert_eq!(board.covered_count(), 1);
+}
+
+#[test]
+fn mask_shapes_random_boards() {
+    let dir = tempfile::tempdir().unwrap();
+    let mask = write_file(&dir, "mask.txt", RING);
+    let board = TestBoard::with_options("mask", WALL, |options| {
+        options.source = BoardSource::Random;
+        options.shape = BoardShape::Mask(mask);
+        // 配置中的尺寸放不下这么多炸弹，但是棋盘尺寸以遮罩为准
+        options.map_size = (2, 2);
+        options.bomb_count = 3;
+    });
+    let tile_map = board.board().game.tile_map();
+    assert_eq!((tile_map.width(), tile_map.height()), (5, 5));
+    assert_eq!(tile_map.tile_count(), 16);
+    assert_eq!(board.covers(), 16);
+    assert_eq!(board.ghosts(), 0);
+}
+
+#[test]
+fn safe_start_waits_for_the_first_click() {
+    let mut board = TestBoard::with_options("safe-start", WALL, |options| {
+        options.source = BoardSource::Random;
+        options.map_size = (9, 9);
+        options.bomb_count = 10;
+        options.safe_start = true;
+    });
+    assert!(board.board().game.is_generated());
+    assert!(board.covered_count() < 81);
+
+    // 自动揭开的起点不算玩家的操作，计时器还没有开始
+    board.app.update();
+    assert!(!board.board().started);
+    assert_eq!(board.board().elapsed, 0.);
+
+    let (coords, _) = board.board().hint().unwrap();
+    board.trigger(coords.x, coords.y);
+    assert!(board.board().started);
+}
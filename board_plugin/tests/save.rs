@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use board_plugin::resources::{BoardOptions, BoardSource, SaveGame};
+use minesweeper_core::{Coordinates, Game, Generation, TileMap};
+
+/// 以前的版本写入的存档：配置中没有后来增加的选项，也没有点击次数
+#[test]
+fn old_saves_still_load() {
+    let options = BoardOptions {
+        map_size: (9, 9),
+        bomb_count: 10,
+        ..Default::default()
+    };
+    let mut game = Game::new((9, 9), 10, Generation::Random, 1);
+    game.reveal(Coordinates { x: 4, y: 4 });
+    let save = SaveGame {
+        options,
+        game,
+        elapsed: 12.5,
+        hints_used: 1,
+        clicks: Default::default(),
+        flagged: false,
+    };
+
+    let mut value = serde_json::to_value(&save).unwrap();
+    let object = value.as_object_mut().unwrap();
+    object.remove("clicks");
+    object.remove("flagged");
+    let options = object["options"].as_object_mut().unwrap();
+    for key in [
+        "source",
+        "reveal_delay",
+        "topology",
+        "wrap",
+        "shape",
+        "mines_per_tile",
+    ] {
+        assert!(options.remove(key).is_some(), "配置中没有{}", key);
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("savegame.json");
+    std::fs::write(&path, value.to_string()).unwrap();
+    let loaded = SaveGame::load(&path).unwrap();
+    assert!(matches!(loaded.options.source, BoardSource::Random));
+    assert_eq!(loaded.options.mines_per_tile, 1);
+    assert_eq!(loaded.clicks.total(), 0);
+    assert_eq!(loaded.elapsed, 12.5);
+    assert_eq!(loaded.game.covered().count(), save.game.covered().count());
+}
+
+/// 从文件读取的棋盘可以比随机生成的棋盘更密集，存档之后仍然可以继续
+#[test]
+fn dense_imported_boards_can_be_resumed() {
+    let tile_map = TileMap::from_text("**.\n*..\n.**").unwrap();
+    let mut game = Game::from_tile_map(tile_map, 0);
+    game.reveal(Coordinates { x: 2, y: 2 });
+    let save = SaveGame {
+        options: BoardOptions {
+            map_size: (3, 3),
+            bomb_count: 5,
+            source: BoardSource::File(PathBuf::from("board.txt")),
+            ..Default::default()
+        },
+        game,
+        elapsed: 1.0,
+        hints_used: 0,
+        clicks: Default::default(),
+        flagged: false,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("savegame.json");
+    save.save(&path).unwrap();
+    let loaded = SaveGame::load(&path).unwrap();
+    assert_eq!(loaded.game.tile_map().bomb_count(), 5);
+}
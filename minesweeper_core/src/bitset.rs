@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+/// 定长的位集合，棋盘上的每个棋子对应一位，下标和`TileMap`中的下标一致
+///
+/// 同时记录被设置的位数，计数是O(1)的
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitSet {
+    len: usize,
+    count: usize,
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// 所有位都没有设置
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            count: 0,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    /// 所有位都已经设置
+    pub fn full(len: usize) -> Self {
+        let mut set = Self::new(len);
+        for (i, word) in set.words.iter_mut().enumerate() {
+            let bits = (len - i * 64).min(64);
+            *word = u64::MAX >> (64 - bits);
+        }
+        set.count = len;
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 被设置的位数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// 设置一位，返回这一位原来是否没有设置
+    pub fn insert(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "下标{}超出了范围{}", index, self.len);
+        let word = &mut self.words[index / 64];
+        let mask = 1 << (index % 64);
+        if *word & mask != 0 {
+            return false;
+        }
+        *word |= mask;
+        self.count += 1;
+        true
+    }
+
+    /// 清除一位，返回这一位原来是否已经设置
+    pub fn remove(&mut self, index: usize) -> bool {
+        if !self.contains(index) {
+            return false;
+        }
+        self.words[index / 64] &= !(1 << (index % 64));
+        self.count -= 1;
+        true
+    }
+
+    /// 所有被设置的位的下标，从小到大
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// 检查长度、计数和超出长度的位，反序列化得到的集合可能被修改过
+    pub fn is_valid(&self) -> bool {
+        let tail = self.len % 64;
+        self.words.len() == self.len.div_ceil(64)
+            && self
+                .words
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>()
+                == self.count
+            && (tail == 0 || self.words.last().is_some_and(|w| w >> tail == 0))
+    }
+}
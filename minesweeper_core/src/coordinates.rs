@@ -3,10 +3,13 @@ use std::{
     ops::{Add, Sub},
 };
 
-use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+#[cfg_attr(feature = "bevy", derive(bevy_ecs::component::Component))]
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
 pub struct Coordinates {
     pub x: u16,
     pub y: u16,
@@ -23,13 +26,14 @@ impl Add for Coordinates {
     }
 }
 
-impl Add<(i8, i8)> for Coordinates {
-    type Output = Self;
-
-    fn add(self, (x, y): (i8, i8)) -> Self::Output {
-        let x = (self.x as i16 + x as i16) as u16;
-        let y = (self.y as i16 + y as i16) as u16;
-        Self { x, y }
+impl Coordinates {
+    /// 按照偏移量移动，结果小于0或者超出`u16`时返回`None`
+    /// 不检查是否在棋盘内，需要棋盘内的邻居时使用`TileMap::neighbors`
+    pub fn checked_offset(self, (dx, dy): (i8, i8)) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add_signed(dx as i16)?,
+            y: self.y.checked_add_signed(dy as i16)?,
+        })
     }
 }
 
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// 难度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// 初级 9x9，10个炸弹
+    Beginner,
+    /// 中级 16x16，40个炸弹
+    Intermediate,
+    /// 高级 30x16，99个炸弹
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Beginner, Self::Intermediate, Self::Expert];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Beginner => "Beginner",
+            Self::Intermediate => "Intermediate",
+            Self::Expert => "Expert",
+        }
+    }
+
+    /// 棋盘大小
+    pub fn map_size(&self) -> (u16, u16) {
+        match self {
+            Self::Beginner => (9, 9),
+            Self::Intermediate => (16, 16),
+            Self::Expert => (30, 16),
+        }
+    }
+
+    /// 炸弹数量
+    pub fn bomb_count(&self) -> u16 {
+        match self {
+            Self::Beginner => 10,
+            Self::Intermediate => 40,
+            Self::Expert => 99,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! 棋盘的交换格式
+//!
+//! - MBF：二进制格式，宽、高各一个字节，炸弹数量两个字节（大端），之后每个炸弹两个字节(x, y)
+//!   不支持空洞，也不支持一个棋子上有多个炸弹
+//! - 文本：每行一排棋子，`*`是炸弹，`.`是安全的棋子，`#`是空洞，`2`、`3`是有多个炸弹的棋子
+//! - 棋盘代码：宽、高各两个字节（大端），之后每个棋子一个比特，用 base64 编码，方便复制分享
+//!   有空洞时再跟上每个位置一个比特，表示是不是空洞
+//!   有多个炸弹的棋子时，空洞之后再按顺序跟上每个炸弹棋子上炸弹的数量，各一个字节
+//!
+//! 这些格式的第一行都是最上面的一排，而棋盘的y轴向上，读写时需要翻转
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    path::Path,
+};
+
+use crate::{Coordinates, TileMap};
+
+/// 棋盘格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardFormat {
+    Mbf,
+    Text,
+    Code,
+}
+
+impl BoardFormat {
+    /// 根据扩展名判断格式，`.mbf`为MBF，`.txt`为文本，其他都当做棋盘代码
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path.as_ref().extension().and_then(|e| e.to_str());
+        match extension.map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("mbf") => Self::Mbf,
+            Some("txt") => Self::Text,
+            _ => Self::Code,
+        }
+    }
+}
+
+/// 读取棋盘失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// 数据在读完之前就结束了
+    Truncated,
+    /// 棋盘的宽度或者高度为0
+    InvalidSize { width: u16, height: u16 },
+    /// 格式无法表示这么大的棋盘
+    TooLarge { width: u16, height: u16 },
+    /// 炸弹太多
+    TooManyMines,
+    /// 炸弹不在棋盘内
+    MineOutOfBounds(Coordinates),
+    /// 同一个位置有两个炸弹
+    DuplicateMine(Coordinates),
+    /// 文本中有无法识别的字符
+    InvalidCharacter(char),
+    /// 文本中每一行的长度不一样
+    RaggedRows,
+    /// 棋盘代码不是有效的 base64
+    InvalidCode,
+    /// 空洞不在棋盘内
+    VoidOutOfBounds(Coordinates),
+    /// 空洞上有炸弹
+    MineInVoid(Coordinates),
+    /// 格式无法表示空洞
+    VoidUnsupported,
+    /// 一个棋子上的炸弹超过了`Tile::MAX_MINES`
+    TooManyMinesInTile(Coordinates),
+    /// 格式无法表示一个棋子上有多个炸弹
+    MultiMineUnsupported,
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "数据不完整"),
+            Self::InvalidSize { width, height } => {
+                write!(f, "棋盘尺寸({}, {})无效", width, height)
+            }
+            Self::TooLarge { width, height } => {
+                write!(f, "棋盘尺寸({}, {})超出了格式的范围", width, height)
+            }
+            Self::TooManyMines => write!(f, "炸弹数量太多"),
+            Self::MineOutOfBounds(coords) => write!(f, "炸弹{}不在棋盘内", coords),
+            Self::DuplicateMine(coords) => write!(f, "炸弹{}重复", coords),
+            Self::InvalidCharacter(c) => write!(f, "无法识别的字符: {:?}", c),
+            Self::RaggedRows => write!(f, "每一行的长度必须相同"),
+            Self::InvalidCode => write!(f, "无效的棋盘代码"),
+            Self::VoidOutOfBounds(coords) => write!(f, "空洞{}不在棋盘内", coords),
+            Self::MineInVoid(coords) => write!(f, "空洞{}上有炸弹", coords),
+            Self::VoidUnsupported => write!(f, "这个格式不支持空洞"),
+            Self::TooManyMinesInTile(coords) => write!(f, "棋子{}上的炸弹太多", coords),
+            Self::MultiMineUnsupported => write!(f, "这个格式不支持一个棋子上有多个炸弹"),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl TileMap {
+    /// 按照格式读取棋盘
+    pub fn read(data: &[u8], format: BoardFormat) -> Result<Self, FormatError> {
+        match format {
+            BoardFormat::Mbf => Self::from_mbf(data),
+            BoardFormat::Text => Self::from_text(&String::from_utf8_lossy(data)),
+            BoardFormat::Code => Self::from_code(&String::from_utf8_lossy(data)),
+        }
+    }
+
+    /// 按照格式写出棋盘
+    pub fn write(&self, format: BoardFormat) -> Result<Vec<u8>, FormatError> {
+        match format {
+            BoardFormat::Mbf => self.to_mbf(),
+            BoardFormat::Text => Ok(self.to_text().into_bytes()),
+            BoardFormat::Code => Ok(self.to_code().into_bytes()),
+        }
+    }
+
+    pub fn from_mbf(data: &[u8]) -> Result<Self, FormatError> {
+        let header = data.get(..4).ok_or(FormatError::Truncated)?;
+        let (width, height) = (header[0] as u16, header[1] as u16);
+        let count = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let body = data.get(4..4 + count * 2).ok_or(FormatError::Truncated)?;
+        let mines = body.chunks(2).map(|pair| Coordinates {
+            x: pair[0] as u16,
+            // 越界的坐标翻转后仍然越界，交给 from_mines 检查
+            y: height.wrapping_sub(1).wrapping_sub(pair[1] as u16),
+        });
+        Self::from_mines(width, height, mines)
+    }
+
+    pub fn to_mbf(&self) -> Result<Vec<u8>, FormatError> {
+        let (width, height) = (self.width(), self.height());
+        if width > u8::MAX as u16 || height > u8::MAX as u16 {
+            return Err(FormatError::TooLarge { width, height });
+        }
+        if self.void().next().is_some() {
+            return Err(FormatError::VoidUnsupported);
+        }
+        if self.bomb_count() as usize != self.mine_tile_count() {
+            return Err(FormatError::MultiMineUnsupported);
+        }
+        let mut data = vec![width as u8, height as u8];
+        data.extend_from_slice(&self.bomb_count().to_be_bytes());
+        for coords in self.mines() {
+            data.push(coords.x as u8);
+            data.push((height - 1 - coords.y) as u8);
+        }
+        Ok(data)
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, FormatError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if rows.iter().any(|row| row.chars().count() != width) {
+            return Err(FormatError::RaggedRows);
+        }
+        if width > u16::MAX as usize || rows.len() > u16::MAX as usize {
+            return Err(FormatError::TooLarge {
+                width: width.min(u16::MAX as usize) as u16,
+                height: rows.len().min(u16::MAX as usize) as u16,
+            });
+        }
+        let (width, height) = (width as u16, rows.len() as u16);
+
+        let mut mines = Vec::new();
+        let mut void = Vec::new();
+        for (row, line) in rows.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let coords = Coordinates {
+                    x: x as u16,
+                    y: height - 1 - row as u16,
+                };
+                match c {
+                    '*' => mines.push((coords, 1)),
+                    '#' => void.push(coords),
+                    '.' => (),
+                    c => match c.to_digit(10) {
+                        Some(count @ 2..=9) => mines.push((coords, count as u8)),
+                        _ => return Err(FormatError::InvalidCharacter(c)),
+                    },
+                }
+            }
+        }
+        Self::from_mine_counts(width, height, mines)?.with_void(void)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for y in (0..self.height()).rev() {
+            for x in 0..self.width() {
+                let coords = Coordinates { x, y };
+                text.push(match self.mines_at(coords) {
+                    _ if self.is_void_at(coords) => '#',
+                    0 => '.',
+                    1 => '*',
+                    count => char::from_digit(count as u32, 10).unwrap_or('*'),
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn from_code(code: &str) -> Result<Self, FormatError> {
+        let data = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+            .map_err(|_| FormatError::InvalidCode)?;
+        let header = data.get(..4).ok_or(FormatError::Truncated)?;
+        let width = u16::from_be_bytes([header[0], header[1]]);
+        let height = u16::from_be_bytes([header[2], header[3]]);
+        let cells = width as usize * height as usize;
+        let size = cells.div_ceil(8);
+        let bits = &data[4..];
+        if bits.len() < size {
+            return Err(FormatError::Truncated);
+        }
+        let (mines, rest) = bits.split_at(size);
+        // 没有空洞和多个炸弹的棋盘代码只有一段
+        if !rest.is_empty() && rest.len() < size {
+            return Err(FormatError::Truncated);
+        }
+        let (void, counts) = rest.split_at(rest.len().min(size));
+        let coords = |i: usize| Coordinates {
+            x: (i % width as usize) as u16,
+            y: height - 1 - (i / width as usize) as u16,
+        };
+        let is_set = |bits: &[u8], i: usize| bits[i / 8] & (0x80 >> (i % 8)) != 0;
+        let mines: Vec<usize> = (0..cells).filter(|&i| is_set(mines, i)).collect();
+        if !counts.is_empty() && counts.len() < mines.len() {
+            return Err(FormatError::Truncated);
+        }
+        let count = |n: usize| counts.get(n).copied().unwrap_or(1);
+        let tile_map = Self::from_mine_counts(
+            width,
+            height,
+            mines
+                .iter()
+                .enumerate()
+                .map(|(n, &i)| (coords(i), count(n))),
+        )?;
+        if void.is_empty() {
+            return Ok(tile_map);
+        }
+        tile_map.with_void((0..cells).filter(|&i| is_set(void, i)).map(coords))
+    }
+
+    pub fn to_code(&self) -> String {
+        let (width, height) = (self.width(), self.height());
+        let size = (width as usize * height as usize).div_ceil(8);
+        let mut mines = vec![0u8; size];
+        let mut void = vec![0u8; size];
+        let rows = (0..height)
+            .rev()
+            .flat_map(|y| (0..width).map(move |x| Coordinates { x, y }));
+        for (i, coords) in rows.enumerate() {
+            if self.is_bomb_at(coords) {
+                mines[i / 8] |= 0x80 >> (i % 8);
+            }
+            if self.is_void_at(coords) {
+                void[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        let multi_mine = self.bomb_count() as usize != self.mine_tile_count();
+        let mut data = Vec::with_capacity(4 + 2 * size);
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend(mines);
+        if multi_mine || self.void().next().is_some() {
+            data.extend(void);
+        }
+        if multi_mine {
+            // 和炸弹的比特顺序一致，从最上面一行开始
+            let mut mines: Vec<Coordinates> = self.mines().collect();
+            mines.sort_by_key(|c| (height - 1 - c.y, c.x));
+            data.extend(mines.iter().map(|c| self.mines_at(*c)));
+        }
+        base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+    }
+}
@@ -0,0 +1,465 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitset::BitSet, solver::Knowledge, Coordinates, FormatError, Generation, Tile, TileMap,
+    Topology,
+};
+
+/// 游戏结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameOutcome {
+    /// 游戏进行中
+    Playing,
+    /// 揭开了所有安全的棋子
+    Won,
+    /// 揭开了炸弹，记录爆炸的坐标
+    Lost(Coordinates),
+}
+
+/// 揭开一个棋子的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealOutcome {
+    /// 棋子已经揭开、被标记、不在棋盘上，游戏已经结束，或者放不下这么多炸弹，什么也没有发生
+    Ignored,
+    /// 揭开了一个安全的棋子
+    /// `revealed`是这一次揭开的所有棋子，空白棋子周围的棋子会一起揭开，按照离点击的棋子由近到远排列
+    Revealed { revealed: Vec<Coordinates> },
+    /// 揭开了炸弹，游戏失败
+    Exploded,
+    /// 揭开了最后一个安全的棋子，游戏胜利
+    /// `flagged`是胜利时被自动标记的炸弹，包括旗子数量被修正的炸弹
+    Won {
+        revealed: Vec<Coordinates>,
+        flagged: Vec<Coordinates>,
+    },
+}
+
+/// 无法开始游戏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// 棋盘的宽度或者高度为0
+    EmptyMap { width: u16, height: u16 },
+    /// 炸弹数量必须小于`limit`
+    TooManyMines { bomb_count: u16, limit: u32 },
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyMap { width, height } => {
+                write!(f, "棋盘尺寸({}, {})无效", width, height)
+            }
+            Self::TooManyMines { bomb_count, limit } => {
+                write!(f, "炸弹数量{}太多，必须小于{}", bomb_count, limit)
+            }
+        }
+    }
+}
+
+impl Error for GameError {}
+
+/// 一局游戏的状态：棋盘、哪些棋子还没有揭开、哪些棋子被标记以及游戏结果
+///
+/// 所有的规则（揭开、标记、双击、胜负）都在这里，渲染只需要根据返回的结果更新画面
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Game {
+    tile_map: TileMap,
+    bomb_count: u16,
+    generation: Generation,
+    /// 生成棋盘使用的随机数种子，和第一次揭开的坐标一起决定炸弹的位置
+    seed: u64,
+    /// 是否已经放置了炸弹，炸弹会在第一次揭开棋子的时候才放置
+    generated: bool,
+    /// 没有揭开的棋子，下标和`TileMap`一致
+    covered: BitSet,
+    /// 被标记的棋子，一定是没有揭开的棋子
+    marked: BitSet,
+    /// 被标记的棋子上旗子的数量，只记录多于一个旗子的棋子
+    #[serde(default)]
+    flags: BTreeMap<usize, u8>,
+    /// 每个棋子上最多的炸弹数量，也是一个棋子上最多的旗子数量
+    #[serde(default = "one_mine_per_tile")]
+    mines_per_tile: u8,
+    outcome: GameOutcome,
+}
+
+fn one_mine_per_tile() -> u8 {
+    1
+}
+
+impl Game {
+    /// 第一次揭开的棋子及其周围最多的棋子数量，这些棋子上不会放置炸弹
+    pub const SAFE_AREA: usize = 9;
+
+    /// 有`tile_count`个棋子、每个棋子最多`mines_per_tile`个炸弹时，炸弹数量必须小于这个值
+    /// 无论第一次揭开哪个棋子，都有足够的位置放置炸弹
+    pub fn mine_limit(tile_count: usize, mines_per_tile: u8) -> u32 {
+        tile_count.saturating_sub(Self::SAFE_AREA) as u32 * mines_per_tile as u32
+    }
+
+    /// 创建一局新游戏，所有的棋子都没有揭开
+    pub fn new(map_size: (u16, u16), bomb_count: u16, generation: Generation, seed: u64) -> Self {
+        let (width, height) = map_size;
+        let len = width as usize * height as usize;
+        Self {
+            tile_map: TileMap::empty(width, height),
+            bomb_count,
+            generation,
+            seed,
+            generated: false,
+            covered: BitSet::full(len),
+            marked: BitSet::new(len),
+            flags: BTreeMap::new(),
+            mines_per_tile: 1,
+            outcome: GameOutcome::Playing,
+        }
+    }
+
+    /// 使用已经放置好炸弹的棋盘创建一局新游戏，比如从文件读取的棋盘
+    /// 这样的棋盘不保证第一次揭开的棋子是安全的
+    pub fn from_tile_map(tile_map: TileMap, seed: u64) -> Self {
+        let mut game = Self::new(
+            (tile_map.width(), tile_map.height()),
+            tile_map.bomb_count(),
+            Generation::Random,
+            seed,
+        );
+        game.mines_per_tile = tile_map
+            .tiles()
+            .map(|(_, t)| t.mines())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        game.tile_map = tile_map;
+        game.generated = true;
+        game.remove_void();
+        game
+    }
+
+    /// 使用另一种相邻关系，需要在揭开任何棋子之前调用
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.tile_map = self.tile_map.with_topology(topology);
+        self
+    }
+
+    /// 让棋盘的边缘相连，需要在揭开任何棋子之前调用
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.tile_map = self.tile_map.with_wrap(wrap);
+        self
+    }
+
+    /// 每个棋子上最多放置`mines_per_tile`个炸弹，需要在揭开任何棋子之前调用
+    /// 数量限制在1到`Tile::MAX_MINES`之间
+    pub fn with_mines_per_tile(mut self, mines_per_tile: u8) -> Self {
+        self.mines_per_tile = mines_per_tile.clamp(1, Tile::MAX_MINES);
+        self
+    }
+
+    /// 把这些位置变为空洞，需要在揭开任何棋子之前调用
+    /// 位置不在棋盘内或者已经放置的炸弹在空洞上时返回错误
+    pub fn with_void(
+        mut self,
+        void: impl IntoIterator<Item = Coordinates>,
+    ) -> Result<Self, FormatError> {
+        self.tile_map = self.tile_map.with_void(void)?;
+        self.remove_void();
+        Ok(self)
+    }
+
+    /// 空洞不需要揭开，也不能标记
+    fn remove_void(&mut self) {
+        let void: Vec<usize> = self
+            .tile_map
+            .void()
+            .filter_map(|c| self.tile_map.index_of(c))
+            .collect();
+        for i in void {
+            self.covered.remove(i);
+            self.marked.remove(i);
+            self.flags.remove(&i);
+        }
+    }
+
+    /// 检查棋盘尺寸和炸弹数量，需要在设置完空洞和每个棋子的炸弹数量之后调用
+    /// 已经放置了炸弹的游戏总是有效的
+    pub fn validate(&self) -> Result<(), GameError> {
+        let (width, height) = (self.tile_map.width(), self.tile_map.height());
+        if width == 0 || height == 0 {
+            return Err(GameError::EmptyMap { width, height });
+        }
+        if self.generated {
+            return Ok(());
+        }
+        let limit = Self::mine_limit(self.tile_map.tile_count(), self.mines_per_tile);
+        if self.bomb_count as u32 >= limit {
+            return Err(GameError::TooManyMines {
+                bomb_count: self.bomb_count,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    /// 检查游戏状态是否一致，反序列化得到的游戏可能被修改过
+    pub fn is_valid(&self) -> bool {
+        let len = self.tile_map.len();
+        self.tile_map.is_valid()
+            && (!self.generated || self.tile_map.bomb_count() == self.bomb_count)
+            && self.covered.len() == len
+            && self.marked.len() == len
+            && self.covered.is_valid()
+            && self.marked.is_valid()
+            && self.marked.iter().all(|i| self.covered.contains(i))
+            && (1..=Tile::MAX_MINES).contains(&self.mines_per_tile)
+            && self
+                .flags
+                .iter()
+                .all(|(i, n)| self.marked.contains(*i) && (2..=self.mines_per_tile).contains(n))
+            && self.tile_map.void().all(|c| !self.is_covered_at(c))
+    }
+
+    pub fn tile_map(&self) -> &TileMap {
+        &self.tile_map
+    }
+
+    /// 随机数种子，第一次揭开不同的棋子时，相同的种子可能生成不同的棋盘
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    pub fn outcome(&self) -> GameOutcome {
+        self.outcome
+    }
+
+    pub fn mines_per_tile(&self) -> u8 {
+        self.mines_per_tile
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.outcome == GameOutcome::Playing
+    }
+
+    /// 还没有揭开的棋子
+    pub fn covered(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.covered.iter().map(|i| self.tile_map.coords_of(i))
+    }
+
+    /// 被标记的棋子
+    pub fn marked(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.marked.iter().map(|i| self.tile_map.coords_of(i))
+    }
+
+    pub fn is_covered_at(&self, coords: Coordinates) -> bool {
+        self.tile_map
+            .index_of(coords)
+            .is_some_and(|i| self.covered.contains(i))
+    }
+
+    pub fn is_marked_at(&self, coords: Coordinates) -> bool {
+        self.tile_map
+            .index_of(coords)
+            .is_some_and(|i| self.marked.contains(i))
+    }
+
+    /// 棋子上旗子的数量，没有标记时为0
+    pub fn mark_count_at(&self, coords: Coordinates) -> u8 {
+        match self.tile_map.index_of(coords) {
+            Some(i) if self.marked.contains(i) => self.flags.get(&i).copied().unwrap_or(1),
+            _ => 0,
+        }
+    }
+
+    pub fn is_bomb_at(&self, coords: Coordinates) -> bool {
+        self.tile_map.is_bomb_at(coords)
+    }
+
+    /// 剩余的炸弹数量，标记过多时为负数
+    pub fn remaining_mines(&self) -> i32 {
+        let extra: i32 = self.flags.values().map(|n| *n as i32 - 1).sum();
+        self.bomb_count as i32 - self.marked.count() as i32 - extra
+    }
+
+    /// 只剩下炸弹没有揭开
+    pub fn is_completed(&self) -> bool {
+        self.covered.count() == self.tile_map.mine_tile_count()
+    }
+
+    /// 已经完成的3BV，3BV中所有的棋子都揭开了才算完成
+    pub fn solved_bbbv(&self) -> u32 {
+        self.tile_map
+            .bbbv_units()
+            .iter()
+            .filter(|unit| unit.iter().all(|c| !self.is_covered_at(*c)))
+            .count() as u32
+    }
+
+    /// 以`safe`为中心放置炸弹，保证第一次揭开的棋子及其周围没有炸弹
+    /// 已经放置过炸弹时什么也不做，除去安全区域后放不下这么多炸弹时返回错误
+    /// 炸弹的位置由种子和`safe`共同决定，落在安全区域的炸弹会重新随机放置，要复现棋盘，第一次也要揭开同一个棋子
+    pub fn generate(&mut self, safe: Coordinates) -> Result<(), GameError> {
+        if self.generated {
+            return Ok(());
+        }
+        self.tile_map = self.tile_map.generate(
+            self.bomb_count,
+            self.mines_per_tile,
+            &self.generation,
+            safe,
+            self.seed,
+        )?;
+        self.generated = true;
+        Ok(())
+    }
+
+    /// 揭开一个棋子，还没有放置炸弹时会先以这个棋子为中心放置炸弹
+    /// 空白棋子周围的棋子会在这一次全部揭开，返回时游戏状态已经是最终的结果
+    pub fn reveal(&mut self, coords: Coordinates) -> RevealOutcome {
+        if !self.is_playing() || !self.is_covered_at(coords) || self.is_marked_at(coords) {
+            return RevealOutcome::Ignored;
+        }
+        if let Err(e) = self.generate(coords) {
+            log::error!("无法放置炸弹: {}", e);
+            return RevealOutcome::Ignored;
+        }
+
+        if self.tile_map.is_bomb_at(coords) {
+            self.uncover(coords);
+            self.outcome = GameOutcome::Lost(coords);
+            return RevealOutcome::Exploded;
+        }
+        let revealed = self.flood_fill(coords);
+        if self.is_completed() {
+            self.outcome = GameOutcome::Won;
+            // 剩下的棋子都是炸弹，全部按照炸弹的数量标记出来
+            let unmarked: Vec<usize> = self
+                .covered
+                .iter()
+                .filter(|&i| {
+                    let coords = self.tile_map.coords_of(i);
+                    self.mark_count_at(coords) != self.tile_map.mines_at(coords)
+                })
+                .collect();
+            let mut flagged = Vec::with_capacity(unmarked.len());
+            for i in unmarked {
+                let coords = self.tile_map.coords_of(i);
+                self.marked.insert(i);
+                self.flags.remove(&i);
+                if self.tile_map.mines_at(coords) > 1 {
+                    self.flags.insert(i, self.tile_map.mines_at(coords));
+                }
+                flagged.push(coords);
+            }
+            flagged.sort();
+            return RevealOutcome::Won { revealed, flagged };
+        }
+        RevealOutcome::Revealed { revealed }
+    }
+
+    /// 从`start`开始广度优先揭开棋子，空白棋子会继续揭开周围没有标记的棋子
+    /// 返回按照揭开顺序排列的坐标
+    fn flood_fill(&mut self, start: Coordinates) -> Vec<Coordinates> {
+        let mut revealed = Vec::new();
+        let mut pending = VecDeque::from([start]);
+        self.uncover(start);
+        while let Some(coords) = pending.pop_front() {
+            revealed.push(coords);
+            if self.tile_map[coords] != Tile::Empty {
+                continue;
+            }
+            for c in self.tile_map.neighbors(coords) {
+                let i = self.tile_map.index_of(c).expect("邻居一定在棋盘内");
+                if !self.marked.contains(i) && self.covered.remove(i) {
+                    pending.push_back(c);
+                }
+            }
+        }
+        revealed
+    }
+
+    /// 移除一个棋子的覆盖，返回这个棋子原来是否被覆盖
+    fn uncover(&mut self, coords: Coordinates) -> bool {
+        match self.tile_map.index_of(coords) {
+            Some(i) => self.covered.remove(i),
+            None => false,
+        }
+    }
+
+    /// 在一个没有揭开的棋子上增加一个旗子，旗子的数量超过`mines_per_tile`时取消标记
+    /// 返回标记后旗子的数量，0表示没有标记；棋子已经揭开或者游戏已经结束时返回`None`
+    pub fn toggle_mark(&mut self, coords: Coordinates) -> Option<u8> {
+        if !self.is_playing() || !self.is_covered_at(coords) {
+            return None;
+        }
+        let i = self.tile_map.index_of(coords)?;
+        let count = self.mark_count_at(coords);
+        self.flags.remove(&i);
+        if count >= self.mines_per_tile {
+            self.marked.remove(i);
+            return Some(0);
+        }
+        self.marked.insert(i);
+        if count > 0 {
+            self.flags.insert(i, count + 1);
+        }
+        Some(count + 1)
+    }
+
+    /// 双击一个已经揭开的数字棋子，如果周围旗子的数量等于数字，则返回周围所有没有标记的隐藏棋子
+    /// 标记错误的时候，返回的棋子中会有炸弹
+    pub fn chord(&self, coords: Coordinates) -> Vec<Coordinates> {
+        if !self.is_playing() || self.is_covered_at(coords) {
+            return Vec::new();
+        }
+        let tile = self.tile_map.get(coords);
+        let count = match tile {
+            Some(Tile::BombNeighbor(v)) => *v as usize,
+            _ => return Vec::new(),
+        };
+        let (marked, unmarked): (Vec<Coordinates>, Vec<Coordinates>) = self
+            .tile_map
+            .neighbors(coords)
+            .filter(|c| self.is_covered_at(*c))
+            .partition(|c| self.is_marked_at(*c));
+        let flags: usize = marked.iter().map(|c| self.mark_count_at(*c) as usize).sum();
+        if flags != count {
+            return Vec::new();
+        }
+        unmarked
+    }
+
+    /// 提示一个棋子，优先选择一定不是炸弹的棋子，否则选择是炸弹的概率最低的棋子
+    /// 求解器假设每个棋子最多一个炸弹，一个棋子可以有多个炸弹时只能提示第一次揭开的棋子
+    pub fn hint(&self) -> Option<(Coordinates, f64)> {
+        // 还没有放置炸弹，第一次揭开的棋子一定不是炸弹，选择离中心最近的棋子
+        if !self.generated {
+            let center = Coordinates {
+                x: self.tile_map.width() / 2,
+                y: self.tile_map.height() / 2,
+            };
+            let (coords, _) = self
+                .tile_map
+                .tiles()
+                .min_by_key(|(c, _)| self.tile_map.distance(*c, center))?;
+            return Some((coords, 0.0));
+        }
+        if self.mines_per_tile > 1 {
+            return None;
+        }
+
+        let analysis = Knowledge::new(&self.tile_map, self.covered(), self.marked()).analyze()?;
+        match analysis.safe.first() {
+            Some(coords) => Some((*coords, 0.0)),
+            None => analysis.safest(),
+        }
+    }
+}
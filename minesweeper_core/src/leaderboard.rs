@@ -0,0 +1,215 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Difficulty;
+
+/// 排行榜的分类：预设的难度，或者自定义的棋盘大小和炸弹数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScoreCategory {
+    Preset(Difficulty),
+    Custom {
+        width: u16,
+        height: u16,
+        bomb_count: u16,
+    },
+}
+
+impl ScoreCategory {
+    /// 和预设难度相同的棋盘归入这个难度
+    pub fn new((width, height): (u16, u16), bomb_count: u16) -> Self {
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.map_size() == (width, height) && d.bomb_count() == bomb_count)
+            .map_or(
+                Self::Custom {
+                    width,
+                    height,
+                    bomb_count,
+                },
+                Self::Preset,
+            )
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Preset(difficulty) => difficulty.name().to_string(),
+            Self::Custom {
+                width,
+                height,
+                bomb_count,
+            } => format!("Custom {}x{} / {}", width, height, bomb_count),
+        }
+    }
+}
+
+/// 一次胜利的成绩
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub category: ScoreCategory,
+    /// 用时，秒
+    pub time: f32,
+    /// 每秒完成的3BV
+    pub bbbv_per_second: f32,
+    /// 棋盘的随机数种子，第一次揭开同一个棋子才能得到相同的棋盘
+    pub seed: u64,
+    /// 日期，从1970-01-01开始的秒数
+    pub date: u64,
+}
+
+impl Score {
+    /// 现在获得的成绩
+    pub fn now(category: ScoreCategory, time: f32, bbbv_per_second: f32, seed: u64) -> Self {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            category,
+            time,
+            bbbv_per_second,
+            seed,
+            date,
+        }
+    }
+
+    /// 格式化为`YYYY-MM-DD`（UTC）
+    pub fn date_string(&self) -> String {
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = (self.date / 86400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let doe = days.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// 损坏的文件中可能有无效的成绩
+    fn is_valid(&self) -> bool {
+        self.time.is_finite() && self.time >= 0. && self.bbbv_per_second.is_finite()
+    }
+}
+
+/// 本地排行榜，每个分类保存用时最短的几次成绩
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    /// 每个分类最多保存的成绩
+    pub const MAX_SCORES: usize = 10;
+
+    /// 用户数据目录中的排行榜文件
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("minesweeper").join("leaderboard.json"))
+    }
+
+    /// 读取排行榜，文件不存在时返回空的排行榜
+    /// 文件损坏时，把它重命名为`*.corrupt`保留下来，并返回空的排行榜，不影响继续记录成绩
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    log::warn!("无法读取排行榜{}: {}", path.display(), e);
+                }
+                return Self::default();
+            }
+        };
+        match serde_json::from_str::<Self>(&data) {
+            Ok(mut leaderboard) => {
+                leaderboard.normalize();
+                leaderboard
+            }
+            Err(e) => {
+                let backup = path.with_extension("json.corrupt");
+                log::warn!("排行榜已损坏({})，备份到{}", e, backup.display());
+                if let Err(e) = fs::rename(path, &backup) {
+                    log::warn!("备份排行榜失败: {}", e);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// 保存排行榜，先写入临时文件再替换，避免写到一半时损坏原来的文件
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let data = serde_json::to_string_pretty(self)?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, data)?;
+        fs::rename(temp, path)
+    }
+
+    /// 记录一次成绩，返回在这个分类中的名次（从0开始），没有进入排行榜时返回`None`
+    /// 用时相同时，先取得的成绩排在前面
+    pub fn record(&mut self, score: Score) -> Option<usize> {
+        if !score.is_valid() {
+            return None;
+        }
+        let category = score.category;
+        let rank = self
+            .scores(category)
+            .iter()
+            .take_while(|s| s.time <= score.time)
+            .count();
+        if rank >= Self::MAX_SCORES {
+            return None;
+        }
+        self.scores.push(score);
+        self.normalize();
+        Some(rank)
+    }
+
+    /// 这个分类的成绩，按用时从短到长排列
+    pub fn scores(&self, category: ScoreCategory) -> Vec<&Score> {
+        self.scores
+            .iter()
+            .filter(|s| s.category == category)
+            .collect()
+    }
+
+    /// 有成绩的分类，预设难度在前
+    pub fn categories(&self) -> Vec<ScoreCategory> {
+        let mut categories: Vec<ScoreCategory> = Vec::new();
+        for score in &self.scores {
+            if !categories.contains(&score.category) {
+                categories.push(score.category);
+            }
+        }
+        categories.sort_by_key(|c| match c {
+            ScoreCategory::Preset(d) => (0, Difficulty::ALL.iter().position(|a| a == d)),
+            ScoreCategory::Custom { .. } => (1, None),
+        });
+        categories
+    }
+
+    /// 去掉无效的成绩，按用时排序，并且每个分类只保留前几名
+    fn normalize(&mut self) {
+        self.scores.retain(Score::is_valid);
+        // 稳定排序，用时相同时保持原来的顺序
+        self.scores.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let mut counts: Vec<(ScoreCategory, usize)> = Vec::new();
+        self.scores.retain(|score| {
+            match counts.iter_mut().find(|(c, _)| *c == score.category) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((score.category, 1)),
+            }
+            counts
+                .iter()
+                .any(|(c, n)| *c == score.category && *n <= Self::MAX_SCORES)
+        });
+    }
+}
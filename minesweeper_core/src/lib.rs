@@ -0,0 +1,22 @@
+//! 扫雷的游戏规则，不依赖 bevy，可以在任何前端中使用
+
+pub mod solver;
+
+mod bitset;
+mod coordinates;
+mod difficulty;
+mod format;
+mod game;
+mod leaderboard;
+mod tile;
+mod tile_map;
+mod topology;
+
+pub use coordinates::Coordinates;
+pub use difficulty::Difficulty;
+pub use format::{BoardFormat, FormatError};
+pub use game::{Game, GameError, GameOutcome, RevealOutcome};
+pub use leaderboard::{Leaderboard, Score, ScoreCategory};
+pub use tile::Tile;
+pub use tile_map::{Generation, TileMap};
+pub use topology::Topology;
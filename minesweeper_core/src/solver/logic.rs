@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use crate::{Coordinates, Tile, TileMap};
+
+use super::Constraint;
+
+/// 逻辑求解器，只使用玩家能看到的信息进行推理
+pub struct Solver<'a> {
+    tile_map: &'a TileMap,
+    /// 已经揭开的棋子
+    revealed: HashSet<Coordinates>,
+    /// 已经确定是炸弹的棋子
+    mines: HashSet<Coordinates>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(tile_map: &'a TileMap) -> Self {
+        Self {
+            tile_map,
+            revealed: HashSet::default(),
+            mines: HashSet::default(),
+        }
+    }
+
+    /// 揭开一个棋子，如果是空白棋子，则连同周围的棋子一起揭开
+    pub fn reveal(&mut self, coords: Coordinates) {
+        let mut stack = vec![coords];
+        while let Some(coords) = stack.pop() {
+            if !self.revealed.insert(coords) {
+                continue;
+            }
+            if let Tile::Empty = self.tile_at(coords) {
+                stack.extend(
+                    self.neighbors(coords)
+                        .filter(|c| !self.revealed.contains(c)),
+                );
+            }
+        }
+    }
+
+    /// 不断地进行推理直到无法继续，返回是否已经揭开了所有安全的棋子
+    pub fn solve(&mut self) -> bool {
+        while self.step() {}
+        self.is_solved()
+    }
+
+    /// 棋子已经揭开，或者已经推理出不是炸弹
+    pub fn is_revealed(&self, coords: Coordinates) -> bool {
+        self.revealed.contains(&coords)
+    }
+
+    /// 棋子已经推理出是炸弹
+    pub fn is_mine(&self, coords: Coordinates) -> bool {
+        self.mines.contains(&coords)
+    }
+
+    fn is_solved(&self) -> bool {
+        self.revealed.len() + self.tile_map.bomb_count() as usize == self.tile_map.tile_count()
+    }
+
+    /// 进行一轮推理，返回是否有新的发现
+    fn step(&mut self) -> bool {
+        let constraints = self.constraints();
+        let mut safe = Vec::new();
+        let mut mines = Vec::new();
+
+        // 单个约束：剩余的炸弹数量为0，或者等于未知棋子的数量
+        for constraint in constraints.iter() {
+            Self::deduce(&constraint.cells, constraint.mines, &mut safe, &mut mines);
+        }
+
+        // 两个约束：如果a是b的子集，则b比a多出来的棋子中有 b.mines - a.mines 个炸弹
+        if safe.is_empty() && mines.is_empty() {
+            for a in constraints.iter() {
+                for b in constraints.iter() {
+                    if a.cells.len() >= b.cells.len()
+                        || !a.cells.iter().all(|c| b.cells.contains(c))
+                    {
+                        continue;
+                    }
+                    let diff: Vec<Coordinates> = b
+                        .cells
+                        .iter()
+                        .filter(|c| !a.cells.contains(c))
+                        .copied()
+                        .collect();
+                    let remaining = b.mines.saturating_sub(a.mines);
+                    Self::deduce(&diff, remaining, &mut safe, &mut mines);
+                }
+            }
+        }
+
+        // 全局约束：所有未知的棋子中，一共还有多少个炸弹
+        if safe.is_empty() && mines.is_empty() {
+            let unknown: Vec<Coordinates> = self.unknown_tiles().collect();
+            let remaining = (self.tile_map.bomb_count() as usize).saturating_sub(self.mines.len());
+            Self::deduce(&unknown, remaining, &mut safe, &mut mines);
+        }
+
+        let progress = !safe.is_empty() || !mines.is_empty();
+        self.mines.extend(mines);
+        for coords in safe {
+            self.reveal(coords);
+        }
+        progress
+    }
+
+    fn deduce(
+        cells: &[Coordinates],
+        mines: usize,
+        safe_out: &mut Vec<Coordinates>,
+        mines_out: &mut Vec<Coordinates>,
+    ) {
+        if cells.is_empty() {
+            return;
+        }
+        if mines == 0 {
+            safe_out.extend_from_slice(cells);
+        } else if mines == cells.len() {
+            mines_out.extend_from_slice(cells);
+        }
+    }
+
+    /// 根据已经揭开的数字棋子，计算所有的约束
+    fn constraints(&self) -> Vec<Constraint> {
+        self.revealed
+            .iter()
+            .filter_map(|coords| match self.tile_at(*coords) {
+                Tile::BombNeighbor(count) => {
+                    let mut cells = Vec::new();
+                    let mut known_mines = 0;
+                    for neighbor in self.neighbors(*coords) {
+                        if self.mines.contains(&neighbor) {
+                            known_mines += 1;
+                        } else if !self.revealed.contains(&neighbor) {
+                            cells.push(neighbor);
+                        }
+                    }
+                    if cells.is_empty() {
+                        return None;
+                    }
+                    Some(Constraint {
+                        cells,
+                        mines: (count as usize).saturating_sub(known_mines),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 既没有揭开，也没有确定是炸弹的棋子
+    fn unknown_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.tile_map
+            .tiles()
+            .map(|(coords, _)| coords)
+            .filter(|c| !self.revealed.contains(c) && !self.mines.contains(c))
+    }
+
+    fn neighbors(&self, coords: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+        self.tile_map.neighbors(coords)
+    }
+
+    fn tile_at(&self, coords: Coordinates) -> Tile {
+        self.tile_map[coords]
+    }
+}
@@ -0,0 +1,20 @@
+pub use logic::Solver;
+pub use probability::{Analysis, Knowledge, MAX_COMPONENT_CELLS};
+
+mod logic;
+mod probability;
+
+use crate::{Coordinates, TileMap};
+
+/// 判断从坐标`start`开始，能否只通过逻辑推理（不需要猜测）揭开所有安全的棋子
+pub fn is_solvable(tile_map: &TileMap, start: Coordinates) -> bool {
+    let mut solver = Solver::new(tile_map);
+    solver.reveal(start);
+    solver.solve()
+}
+
+/// 一个约束：`cells`这些棋子中一共有`mines`个炸弹
+struct Constraint {
+    cells: Vec<Coordinates>,
+    mines: usize,
+}
@@ -0,0 +1,451 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Coordinates, Tile, TileMap};
+
+use super::Constraint;
+
+/// 一组约束最多包含的棋子数量，枚举的时间随着棋子数量指数增长
+/// 超过这个数量时不再枚举，改用近似的概率
+pub const MAX_COMPONENT_CELLS: usize = 24;
+
+/// 玩家当前掌握的信息
+pub struct Knowledge<'a> {
+    /// 棋盘，只会读取已经揭开的棋子
+    tile_map: &'a TileMap,
+    /// 还没有揭开的棋子
+    covered: HashSet<Coordinates>,
+    /// 被标记的棋子，视为炸弹
+    marked: HashSet<Coordinates>,
+}
+
+/// 分析的结果
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    /// 一定不是炸弹的棋子
+    pub safe: Vec<Coordinates>,
+    /// 一定是炸弹的棋子
+    pub mines: Vec<Coordinates>,
+    /// 每个没有揭开也没有被标记的棋子是炸弹的概率
+    pub probabilities: HashMap<Coordinates, f64>,
+    /// 约束太多时概率只是近似值，`safe`和`mines`只包含单个约束就能确定的棋子
+    pub approximate: bool,
+}
+
+impl Analysis {
+    /// 棋子是炸弹的概率
+    pub fn probability(&self, coords: Coordinates) -> Option<f64> {
+        self.probabilities.get(&coords).copied()
+    }
+
+    /// 是炸弹的概率最低的棋子
+    pub fn safest(&self) -> Option<(Coordinates, f64)> {
+        self.probabilities
+            .iter()
+            .map(|(coords, p)| (*coords, *p))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+    }
+}
+
+/// 一组共享棋子的约束，以及它们涉及的棋子
+struct Component {
+    cells: Vec<Coordinates>,
+    /// 约束中的棋子使用在`cells`中的下标表示
+    constraints: Vec<(Vec<usize>, usize)>,
+}
+
+/// 一组约束所有可能的炸弹分布
+struct Solutions {
+    /// `ways[k]`: 一共放置k个炸弹的方案数
+    ways: Vec<f64>,
+    /// `mine_ways[i][k]`: 一共放置k个炸弹，并且第i个棋子是炸弹的方案数
+    mine_ways: Vec<Vec<f64>>,
+}
+
+impl<'a> Knowledge<'a> {
+    pub fn new(
+        tile_map: &'a TileMap,
+        covered: impl IntoIterator<Item = Coordinates>,
+        marked: impl IntoIterator<Item = Coordinates>,
+    ) -> Self {
+        Self {
+            tile_map,
+            covered: covered.into_iter().collect(),
+            marked: marked.into_iter().collect(),
+        }
+    }
+
+    /// 计算每个未知棋子是炸弹的准确概率，包括炸弹总数的约束
+    /// 有一组约束的棋子超过`MAX_COMPONENT_CELLS`时只计算近似的概率
+    /// 如果已知的信息互相矛盾（比如标记错了），则返回`None`
+    pub fn analyze(&self) -> Option<Analysis> {
+        let remaining = (self.tile_map.bomb_count() as usize).checked_sub(self.marked.len())?;
+        let constraints = self.constraints()?;
+        let components = Self::components(&constraints);
+        if components
+            .iter()
+            .any(|c| c.cells.len() > MAX_COMPONENT_CELLS)
+        {
+            log::warn!("约束涉及的棋子太多，只计算近似的概率");
+            return Some(self.approximate(&constraints, remaining));
+        }
+
+        // 不和任何数字相邻的棋子，它们是炸弹的概率都相同
+        let frontier: HashSet<Coordinates> = components
+            .iter()
+            .flat_map(|c| c.cells.iter().copied())
+            .collect();
+        let mut floating: Vec<Coordinates> = self
+            .unknown_tiles()
+            .filter(|c| !frontier.contains(c))
+            .collect();
+        floating.sort();
+
+        let solutions: Vec<Solutions> = components.iter().map(Self::enumerate).collect();
+        let total_ways = solutions
+            .iter()
+            .fold(vec![1.0], |acc, s| convolve(&acc, &s.ways));
+        // floating_ways[t]: 边界上一共有t个炸弹时，剩下的炸弹放在其他棋子上的方案数（相对值）
+        let floating_ways = floating_ways(floating.len(), remaining, frontier.len());
+
+        let total: f64 = total_ways
+            .iter()
+            .zip(floating_ways.iter())
+            .map(|(w, f)| w * f)
+            .sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut analysis = Analysis::default();
+        for (i, (component, solution)) in components.iter().zip(solutions.iter()).enumerate() {
+            // 除了这一组以外，其他所有分组的炸弹分布
+            let others = solutions
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(vec![1.0], |acc, (_, s)| convolve(&acc, &s.ways));
+            let factors: Vec<f64> = (0..solution.ways.len())
+                .map(|k| {
+                    others
+                        .iter()
+                        .enumerate()
+                        .map(|(t, w)| w * floating_ways.get(k + t).copied().unwrap_or(0.0))
+                        .sum()
+                })
+                .collect();
+
+            // 满足所有约束的炸弹数量
+            let possible: Vec<usize> = (0..factors.len())
+                .filter(|k| factors[*k] > 0.0 && solution.ways[*k] > 0.0)
+                .collect();
+            for (cell, mine_ways) in component.cells.iter().zip(solution.mine_ways.iter()) {
+                let probability = if possible.iter().all(|k| mine_ways[*k] == 0.0) {
+                    analysis.safe.push(*cell);
+                    0.0
+                } else if possible.iter().all(|k| mine_ways[*k] == solution.ways[*k]) {
+                    analysis.mines.push(*cell);
+                    1.0
+                } else {
+                    let ways: f64 = mine_ways
+                        .iter()
+                        .zip(factors.iter())
+                        .map(|(m, f)| m * f)
+                        .sum();
+                    ways / total
+                };
+                analysis.probabilities.insert(*cell, probability);
+            }
+        }
+
+        if !floating.is_empty() {
+            let possible: Vec<usize> = (0..total_ways.len())
+                .filter(|t| total_ways[*t] * floating_ways[*t] > 0.0)
+                .collect();
+            let probability = if possible.iter().all(|t| remaining == *t) {
+                0.0
+            } else if possible.iter().all(|t| remaining - t == floating.len()) {
+                1.0
+            } else {
+                let ways: f64 = possible
+                    .iter()
+                    .map(|&t| {
+                        total_ways[t] * floating_ways[t] * (remaining - t) as f64
+                            / floating.len() as f64
+                    })
+                    .sum();
+                ways / total
+            };
+            for cell in floating {
+                if probability == 0.0 {
+                    analysis.safe.push(cell);
+                } else if probability == 1.0 {
+                    analysis.mines.push(cell);
+                }
+                analysis.probabilities.insert(cell, probability);
+            }
+        }
+
+        analysis.safe.sort();
+        analysis.mines.sort();
+        Some(analysis)
+    }
+
+    /// 近似的分析：单个约束确定的棋子，其他棋子的概率取所在约束中最高的炸弹密度
+    /// 不和任何数字相邻的棋子平分剩下的炸弹
+    fn approximate(&self, constraints: &[Constraint], remaining: usize) -> Analysis {
+        let mut safe = HashSet::new();
+        let mut mines = HashSet::new();
+        let mut density: HashMap<Coordinates, f64> = HashMap::default();
+        for constraint in constraints.iter() {
+            if constraint.mines == 0 {
+                safe.extend(constraint.cells.iter().copied());
+            } else if constraint.mines == constraint.cells.len() {
+                mines.extend(constraint.cells.iter().copied());
+            }
+            let p = constraint.mines as f64 / constraint.cells.len() as f64;
+            for cell in constraint.cells.iter() {
+                let entry = density.entry(*cell).or_default();
+                *entry = entry.max(p);
+            }
+        }
+
+        let mut analysis = Analysis {
+            approximate: true,
+            ..Default::default()
+        };
+        let floating: Vec<Coordinates> = self
+            .unknown_tiles()
+            .filter(|c| !density.contains_key(c))
+            .collect();
+        for (cell, p) in density {
+            let probability = if safe.contains(&cell) {
+                0.0
+            } else if mines.contains(&cell) {
+                1.0
+            } else {
+                p
+            };
+            analysis.probabilities.insert(cell, probability);
+        }
+        if !floating.is_empty() {
+            // 剩下的炸弹都已经确定在边界上时，其他棋子一定是安全的
+            let all_found = remaining == mines.len();
+            let expected: f64 = analysis.probabilities.values().sum();
+            let probability = if all_found {
+                0.0
+            } else {
+                ((remaining as f64 - expected) / floating.len() as f64).clamp(0.0, 1.0)
+            };
+            for cell in floating {
+                if all_found {
+                    safe.insert(cell);
+                }
+                analysis.probabilities.insert(cell, probability);
+            }
+        }
+
+        analysis.safe = safe.into_iter().collect();
+        analysis.mines = mines.into_iter().collect();
+        analysis.safe.sort();
+        analysis.mines.sort();
+        analysis
+    }
+
+    /// 既没有揭开，也没有被标记的棋子
+    fn unknown_tiles(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.covered
+            .iter()
+            .copied()
+            .filter(|c| !self.marked.contains(c))
+    }
+
+    /// 根据已经揭开的数字棋子，计算所有的约束
+    fn constraints(&self) -> Option<Vec<Constraint>> {
+        let mut constraints = Vec::new();
+        // 空洞不在`tiles`中，不提供任何信息
+        for (coords, tile) in self.tile_map.tiles() {
+            if self.covered.contains(&coords) {
+                continue;
+            }
+            let count = match *tile {
+                Tile::BombNeighbor(count) => count as usize,
+                Tile::Empty => 0,
+                // 已经揭开的炸弹不提供任何信息
+                Tile::Bomb(_) => continue,
+            };
+            let mut cells = Vec::new();
+            let mut marked = 0;
+            for neighbor in self.tile_map.neighbors(coords) {
+                if self.marked.contains(&neighbor) {
+                    marked += 1;
+                } else if self.covered.contains(&neighbor) {
+                    cells.push(neighbor);
+                }
+            }
+            let mines = count.checked_sub(marked)?;
+            if mines > cells.len() {
+                return None;
+            }
+            if !cells.is_empty() {
+                constraints.push(Constraint { cells, mines });
+            }
+        }
+        Some(constraints)
+    }
+
+    /// 把共享棋子的约束分到同一组，不同的组可以分别计算
+    fn components(constraints: &[Constraint]) -> Vec<Component> {
+        let mut cell_constraints: HashMap<Coordinates, Vec<usize>> = HashMap::default();
+        for (i, constraint) in constraints.iter().enumerate() {
+            for cell in constraint.cells.iter() {
+                cell_constraints.entry(*cell).or_default().push(i);
+            }
+        }
+
+        let mut visited = vec![false; constraints.len()];
+        let mut components = Vec::new();
+        while let Some(start) = visited.iter().position(|v| !v) {
+            visited[start] = true;
+            // 按照广度优先的顺序排列棋子，回溯的时候可以更早地发现矛盾
+            let mut cells: Vec<Coordinates> = Vec::new();
+            let mut indices: HashMap<Coordinates, usize> = HashMap::default();
+            let mut members = vec![start];
+            let mut next = 0;
+            while next < members.len() {
+                for cell in constraints[members[next]].cells.iter() {
+                    if indices.contains_key(cell) {
+                        continue;
+                    }
+                    indices.insert(*cell, cells.len());
+                    cells.push(*cell);
+                    for &other in cell_constraints[cell].iter() {
+                        if !visited[other] {
+                            visited[other] = true;
+                            members.push(other);
+                        }
+                    }
+                }
+                next += 1;
+            }
+            let local = members
+                .iter()
+                .map(|i| {
+                    let constraint = &constraints[*i];
+                    let cells = constraint.cells.iter().map(|c| indices[c]).collect();
+                    (cells, constraint.mines)
+                })
+                .collect();
+            components.push(Component {
+                cells,
+                constraints: local,
+            });
+        }
+        components
+    }
+
+    /// 枚举一组约束所有可能的炸弹分布
+    fn enumerate(component: &Component) -> Solutions {
+        let n = component.cells.len();
+        let mut cell_constraints = vec![Vec::new(); n];
+        for (i, (cells, _)) in component.constraints.iter().enumerate() {
+            for cell in cells.iter() {
+                cell_constraints[*cell].push(i);
+            }
+        }
+        let mut solutions = Solutions {
+            ways: vec![0.0; n + 1],
+            mine_ways: vec![vec![0.0; n + 1]; n],
+        };
+        let mut assignment = vec![None; n];
+        Self::backtrack(
+            component,
+            &cell_constraints,
+            &mut assignment,
+            0,
+            &mut solutions,
+        );
+        solutions
+    }
+
+    fn backtrack(
+        component: &Component,
+        cell_constraints: &[Vec<usize>],
+        assignment: &mut [Option<bool>],
+        index: usize,
+        solutions: &mut Solutions,
+    ) {
+        if index == assignment.len() {
+            let mines: Vec<usize> = (0..assignment.len())
+                .filter(|i| assignment[*i] == Some(true))
+                .collect();
+            solutions.ways[mines.len()] += 1.0;
+            for i in mines.iter() {
+                solutions.mine_ways[*i][mines.len()] += 1.0;
+            }
+            return;
+        }
+
+        for value in [false, true] {
+            assignment[index] = Some(value);
+            // 检查这个棋子涉及的约束是否仍然可以满足
+            let valid = cell_constraints[index].iter().all(|i| {
+                let (cells, mines) = &component.constraints[*i];
+                let mut placed = 0;
+                let mut open = 0;
+                for cell in cells.iter() {
+                    match assignment[*cell] {
+                        Some(true) => placed += 1,
+                        None => open += 1,
+                        Some(false) => (),
+                    }
+                }
+                placed <= *mines && placed + open >= *mines
+            });
+            if valid {
+                Self::backtrack(
+                    component,
+                    cell_constraints,
+                    assignment,
+                    index + 1,
+                    solutions,
+                );
+            }
+        }
+        assignment[index] = None;
+    }
+}
+
+/// 两个方案数分布的卷积
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    result
+}
+
+/// 边界上一共有t个炸弹时，剩下的`remaining - t`个炸弹放在`floating`个棋子上的方案数
+/// 组合数可能非常大，所以在对数空间计算，并且只保留相对的比例
+fn floating_ways(floating: usize, remaining: usize, frontier: usize) -> Vec<f64> {
+    let ln_ways: Vec<f64> = (0..=frontier)
+        .map(|t| match remaining.checked_sub(t) {
+            Some(k) if k <= floating => ln_binomial(floating, k),
+            _ => f64::NEG_INFINITY,
+        })
+        .collect();
+    let max = ln_ways.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return vec![0.0; frontier + 1];
+    }
+    ln_ways.iter().map(|ln| (ln - max).exp()).collect()
+}
+
+/// ln(C(n, k))
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| ((n - k + i) as f64).ln() - (i as f64).ln())
+        .sum()
+}
@@ -0,0 +1,59 @@
+#[cfg(feature = "debug")]
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tile {
+    /// 炸弹棋子，记录这个棋子上炸弹的数量，最多`MAX_MINES`个
+    Bomb(u8),
+    /// 炸弹附近的显示炸弹数量的棋子，一个棋子有多个炸弹时数量可以超过8
+    BombNeighbor(u8),
+    /// 空棋子
+    Empty,
+}
+
+impl Tile {
+    /// 一个棋子上最多的炸弹数量
+    pub const MAX_MINES: u8 = 3;
+
+    /// 判断棋子是否是炸弹
+    pub const fn is_bomb(&self) -> bool {
+        matches!(self, Self::Bomb(_))
+    }
+
+    /// 棋子上炸弹的数量，不是炸弹时为0
+    pub const fn mines(&self) -> u8 {
+        match self {
+            Self::Bomb(count) => *count,
+            _ => 0,
+        }
+    }
+
+    /// 在终端中显示的字符
+    pub fn symbol(&self) -> char {
+        match self {
+            Tile::Bomb(_) => '*',
+            // 只有一个字符，超过9的数量显示为`+`
+            Tile::BombNeighbor(v) => char::from_digit(*v as u32, 10).unwrap_or('+'),
+            Tile::Empty => ' ',
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn console_output(&self) -> String {
+        let symbol = self.symbol().to_string();
+        format!(
+            "{}",
+            match self {
+                Tile::Bomb(_) => symbol.bright_red(),
+                Tile::BombNeighbor(v) => match v {
+                    1 => symbol.cyan(),
+                    2 => symbol.green(),
+                    3 => symbol.yellow(),
+                    _ => symbol.red(),
+                },
+                Tile::Empty => symbol.normal(),
+            }
+        )
+    }
+}
@@ -0,0 +1,572 @@
+use std::ops::Index;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{bitset::BitSet, solver, Coordinates, FormatError, GameError, Tile, Topology};
+
+/// 炸弹的生成方式：随机放置，或者保证不需要猜测
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub enum Generation {
+    /// 随机放置炸弹
+    #[default]
+    Random,
+    /// 只生成不需要猜测就能完成的棋盘
+    /// 尝试`max_attempts`次后仍然失败，则使用最后一次随机生成的棋盘
+    NoGuess { max_attempts: u32 },
+}
+
+/// 定义棋盘
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileMap {
+    /// 炸弹数量，一个棋子上有多个炸弹时分别计数
+    bomb_count: u16,
+    /// 高度
+    height: u16,
+    /// 宽度
+    width: u16,
+    /// 所有的棋子，一行接一行连续存放，坐标`(x, y)`的下标为`y * width + x`
+    tiles: Vec<Tile>,
+    /// 有炸弹的棋子，和`tiles`中的炸弹一致，用来快速判断和计数
+    mines: BitSet,
+    /// 棋子之间的相邻关系，旧的存档中没有这个字段，都是正方形棋盘
+    #[serde(default)]
+    topology: Topology,
+    /// 棋盘的边缘是否相连：最左边一列和最右边一列相邻，最下面一行和最上面一行相邻
+    #[serde(default)]
+    wrap: bool,
+    /// 空洞，不是棋子的位置，用来组成不是矩形的棋盘
+    /// 旧的存档中没有这个字段，长度为0，表示没有空洞
+    #[serde(default)]
+    void: BitSet,
+}
+
+impl TileMap {
+    /// 生成一个空的map
+    pub fn empty(width: u16, height: u16) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            bomb_count: 0,
+            height,
+            width,
+            tiles: vec![Tile::Empty; len],
+            mines: BitSet::new(len),
+            topology: Topology::Square,
+            wrap: false,
+            void: BitSet::new(len),
+        }
+    }
+
+    /// 使用另一种相邻关系，炸弹的位置不变，重新计算每个棋子周围的炸弹数量
+    /// 棋盘文件中只有炸弹的位置，读取后用这个方法指定相邻关系
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        if self.topology != topology {
+            self.topology = topology;
+            self.set_neighbors();
+        }
+        self
+    }
+
+    /// 让棋盘的边缘相连，每个棋子都有完整的邻居，重新计算每个棋子周围的炸弹数量
+    /// 六边形棋盘的高度必须是偶数，否则最上面一行和最下面一行的错开方向相同，无法相连
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        if self.wrap != wrap {
+            self.wrap = wrap;
+            self.set_neighbors();
+        }
+        self
+    }
+
+    /// 把这些位置变为空洞，空洞不是棋子，不会放置炸弹，也不是任何棋子的邻居
+    /// 重新计算每个棋子周围的炸弹数量，位置不在棋盘内或者有炸弹时返回错误
+    pub fn with_void(
+        mut self,
+        void: impl IntoIterator<Item = Coordinates>,
+    ) -> Result<Self, FormatError> {
+        if self.void.len() != self.len() {
+            self.void = BitSet::new(self.len());
+        }
+        for coords in void {
+            let index = self
+                .index_of(coords)
+                .ok_or(FormatError::VoidOutOfBounds(coords))?;
+            if self.mines.contains(index) {
+                return Err(FormatError::MineInVoid(coords));
+            }
+            self.void.insert(index);
+        }
+        self.set_neighbors();
+        Ok(self)
+    }
+
+    /// 使用这个棋盘的尺寸和相邻关系生成新的棋盘，保证坐标`safe`以及它周围的棋子都不是炸弹
+    /// 每个棋子上最多有`max_per_tile`个炸弹
+    /// 相同的尺寸、炸弹数量、生成方式、种子和`safe`总是生成相同的棋盘
+    /// 除去`safe`及其周围的棋子后放不下这么多炸弹时返回错误
+    pub fn generate(
+        &self,
+        bomb_count: u16,
+        max_per_tile: u8,
+        generation: &Generation,
+        safe: Coordinates,
+        seed: u64,
+    ) -> Result<Self, GameError> {
+        // 放不下时每次都会失败，先检查一次
+        self.check_capacity(bomb_count, max_per_tile, &self.safe_area(safe))?;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut random = || {
+            let mut tile_map = self.cleared();
+            tile_map
+                .set_bombs_excluding(bomb_count, max_per_tile, safe, &mut rng)
+                .expect("已经检查过炸弹数量");
+            tile_map
+        };
+
+        let max_attempts = match *generation {
+            Generation::Random => return Ok(random()),
+            // 求解器假设每个棋子最多一个炸弹
+            Generation::NoGuess { .. } if max_per_tile > 1 => {
+                log::warn!("一个棋子有多个炸弹时无法生成无需猜测的棋盘，使用随机棋盘");
+                return Ok(random());
+            }
+            Generation::NoGuess { max_attempts } => max_attempts,
+        };
+        let mut tile_map = random();
+        let mut attempts = 1;
+        while !solver::is_solvable(&tile_map, safe) {
+            if attempts >= max_attempts {
+                log::warn!(
+                    "尝试{}次后仍然无法生成无需猜测的棋盘，使用随机棋盘",
+                    attempts
+                );
+                return Ok(tile_map);
+            }
+            tile_map = random();
+            attempts += 1;
+        }
+        log::info!("尝试{}次后生成了无需猜测的棋盘", attempts);
+        Ok(tile_map)
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn console_output(&self) -> String {
+        // TODO: 优化字符串拼接，这种方式并不高效
+        let mut buffer = format!(
+            "棋盘尺寸: ({}, {})，包含炸弹: {}\n",
+            self.width, self.height, self.bomb_count
+        );
+        let line: String = (0..=(self.width + 1)).map(|_| '-').collect();
+        buffer = format!("{}{}\n", buffer, line);
+        for y in (0..self.height).rev() {
+            buffer = format!("{}|", buffer);
+            for x in 0..self.width {
+                let coords = Coordinates { x, y };
+                if self.is_void_at(coords) {
+                    buffer = format!("{}#", buffer);
+                } else {
+                    buffer = format!("{}{}", buffer, self[coords].console_output());
+                }
+            }
+            buffer = format!("{}|\n", buffer);
+        }
+        format!("{}{}", buffer, line)
+    }
+
+    /// 使用给定的炸弹位置生成棋盘，并计算每个棋子周围的炸弹数量
+    /// 炸弹不在棋盘内或者重复时返回错误
+    pub fn from_mines(
+        width: u16,
+        height: u16,
+        mines: impl IntoIterator<Item = Coordinates>,
+    ) -> Result<Self, FormatError> {
+        Self::from_mine_counts(width, height, mines.into_iter().map(|coords| (coords, 1)))
+    }
+
+    /// 同`from_mines`，同时给出每个棋子上炸弹的数量，数量为0的棋子会被忽略
+    /// 数量超过`Tile::MAX_MINES`时返回错误
+    pub fn from_mine_counts(
+        width: u16,
+        height: u16,
+        mines: impl IntoIterator<Item = (Coordinates, u8)>,
+    ) -> Result<Self, FormatError> {
+        if width == 0 || height == 0 {
+            return Err(FormatError::InvalidSize { width, height });
+        }
+        let mut tile_map = Self::empty(width, height);
+        for (coords, count) in mines.into_iter().filter(|(_, count)| *count > 0) {
+            let index = tile_map
+                .index_of(coords)
+                .ok_or(FormatError::MineOutOfBounds(coords))?;
+            if count > Tile::MAX_MINES {
+                return Err(FormatError::TooManyMinesInTile(coords));
+            }
+            if tile_map.mines.contains(index) {
+                return Err(FormatError::DuplicateMine(coords));
+            }
+            for _ in 0..count {
+                tile_map.place_bomb(index, Tile::MAX_MINES);
+            }
+            tile_map.bomb_count = tile_map
+                .bomb_count
+                .checked_add(count as u16)
+                .ok_or(FormatError::TooManyMines)?;
+        }
+        tile_map.set_neighbors();
+        Ok(tile_map)
+    }
+
+    /// 有炸弹的棋子的数量，一个棋子上有多个炸弹时只算一次
+    pub fn mine_tile_count(&self) -> usize {
+        self.mines.count()
+    }
+
+    /// 有炸弹的棋子的坐标
+    pub fn mines(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.mines.iter().map(|i| self.coords_of(i))
+    }
+
+    /// 所有的棋子和它们的坐标，从最下面一行开始，不包括空洞
+    pub fn tiles(&self) -> impl Iterator<Item = (Coordinates, &Tile)> + '_ {
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.void.contains(*i))
+            .map(|(i, tile)| (self.coords_of(i), tile))
+    }
+
+    /// 所有空洞的坐标
+    pub fn void(&self) -> impl Iterator<Item = Coordinates> + '_ {
+        self.void.iter().map(|i| self.coords_of(i))
+    }
+
+    /// 坐标是不是空洞，不在棋盘内时返回false
+    pub fn is_void_at(&self, coordinates: Coordinates) -> bool {
+        self.index_of(coordinates)
+            .is_some_and(|i| self.void.contains(i))
+    }
+
+    /// 棋子的数量，不包括空洞
+    pub fn tile_count(&self) -> usize {
+        self.len() - self.void.count()
+    }
+
+    /// 每一行的棋子，从最下面一行开始，包括空洞
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Tile]> + '_ {
+        // 宽度为0时没有棋子，chunks不接受0
+        self.tiles.chunks(self.width.max(1) as usize)
+    }
+
+    /// 坐标处的棋子，不在棋盘内时返回`None`
+    pub fn get(&self, coords: Coordinates) -> Option<&Tile> {
+        self.index_of(coords).map(|i| &self.tiles[i])
+    }
+
+    /// 检查棋盘的尺寸和内容是否一致，反序列化得到的棋盘可能被修改过
+    pub fn is_valid(&self) -> bool {
+        let len = self.width as usize * self.height as usize;
+        self.tiles.len() == len
+            && self.mines.len() == len
+            && self.mines.is_valid()
+            && self
+                .tiles
+                .iter()
+                .map(|tile| tile.mines() as usize)
+                .sum::<usize>()
+                == self.bomb_count as usize
+            && self
+                .tiles
+                .iter()
+                .all(|tile| !tile.is_bomb() || (1..=Tile::MAX_MINES).contains(&tile.mines()))
+            && (self.void.len() == 0 || self.void.len() == len)
+            && self.void.is_valid()
+            && self.void.iter().all(|i| !self.mines.contains(i))
+            && self
+                .tiles
+                .iter()
+                .enumerate()
+                .all(|(i, tile)| tile.is_bomb() == self.mines.contains(i))
+    }
+
+    /// 尺寸和相邻关系相同的空棋盘
+    fn cleared(&self) -> Self {
+        Self {
+            topology: self.topology,
+            wrap: self.wrap,
+            void: self.void.clone(),
+            ..Self::empty(self.width, self.height)
+        }
+    }
+
+    /// 坐标在`tiles`中的下标，不在棋盘内时返回`None`
+    pub(crate) fn index_of(&self, coords: Coordinates) -> Option<usize> {
+        if coords.x >= self.width || coords.y >= self.height {
+            return None;
+        }
+        Some(coords.y as usize * self.width as usize + coords.x as usize)
+    }
+
+    /// 下标对应的坐标
+    pub(crate) fn coords_of(&self, index: usize) -> Coordinates {
+        Coordinates {
+            x: (index % self.width as usize) as u16,
+            y: (index / self.width as usize) as u16,
+        }
+    }
+
+    /// 棋子的数量
+    pub(crate) fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn bomb_count(&self) -> u16 {
+        self.bomb_count
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// 棋盘的边缘是否相连
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    /// 棋盘内和坐标相邻的棋子，边上和角上的棋子邻居更少
+    /// 边缘相连的棋盘上每个棋子的邻居都是完整的，除非棋盘太小，绕一圈又回到了同一个棋子
+    pub fn neighbors(&self, coordinates: Coordinates) -> impl Iterator<Item = Coordinates> + '_ {
+        let offsets = self.topology.neighbor_offsets(coordinates);
+        offsets.iter().enumerate().filter_map(move |(i, &offset)| {
+            let c = self.offset(coordinates, offset)?;
+            if self.is_void_at(c) {
+                return None;
+            }
+            let repeated = self.wrap
+                && (c == coordinates
+                    || offsets[..i]
+                        .iter()
+                        .any(|&o| self.offset(coordinates, o) == Some(c)));
+            (!repeated).then_some(c)
+        })
+    }
+
+    /// 按照偏移量移动，边缘相连时从另一边绕回来，否则超出棋盘时返回`None`
+    fn offset(&self, coordinates: Coordinates, (dx, dy): (i8, i8)) -> Option<Coordinates> {
+        if !self.wrap {
+            return coordinates
+                .checked_offset((dx, dy))
+                .filter(|c| c.x < self.width && c.y < self.height);
+        }
+        let wrap = |v: u16, d: i8, size: u16| (v as i32 + d as i32).rem_euclid(size as i32) as u16;
+        Some(Coordinates {
+            x: wrap(coordinates.x, dx, self.width),
+            y: wrap(coordinates.y, dy, self.height),
+        })
+    }
+
+    /// 从一个棋子走到另一个棋子最少需要经过的步数，边缘相连时可以从另一边绕过去
+    pub fn distance(&self, a: Coordinates, b: Coordinates) -> u32 {
+        let (a, b) = ((a.x as i64, a.y as i64), (b.x as i64, b.y as i64));
+        if !self.wrap {
+            return self.topology.offset_distance(a, b);
+        }
+        // 比较绕到棋盘另一边的所有副本
+        let (width, height) = (self.width as i64, self.height as i64);
+        (-1..=1)
+            .flat_map(|kx| (-1..=1).map(move |ky| (b.0 + kx * width, b.1 + ky * height)))
+            .map(|b| self.topology.offset_distance(a, b))
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// 判断一个坐标棋子是不是炸弹
+    pub fn is_bomb_at(&self, coordinates: Coordinates) -> bool {
+        self.index_of(coordinates)
+            .is_some_and(|i| self.mines.contains(i))
+    }
+
+    /// 坐标处棋子上炸弹的数量，不是炸弹或者不在棋盘内时为0
+    pub fn mines_at(&self, coordinates: Coordinates) -> u8 {
+        self.get(coordinates).map_or(0, Tile::mines)
+    }
+
+    /// 计算一个棋子周围有几个炸弹，一个棋子上有多个炸弹时分别计数
+    pub fn bomb_count_at(&self, coordinates: Coordinates) -> u8 {
+        if self.is_bomb_at(coordinates) {
+            return 0;
+        }
+
+        self.neighbors(coordinates)
+            .map(|coord| self.mines_at(coord))
+            .sum()
+    }
+
+    /// 3BV：不使用标记完成棋盘最少需要点击的次数
+    pub fn bbbv(&self) -> u32 {
+        self.bbbv_units().len() as u32
+    }
+
+    /// 3BV的每一次点击会揭开的棋子
+    /// 每一片相连的空白棋子连同它周围的数字算一次，不和空白棋子相邻的数字各算一次
+    pub fn bbbv_units(&self) -> Vec<Vec<Coordinates>> {
+        let mut visited = BitSet::new(self.len());
+        let mut units = Vec::new();
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if *tile != Tile::Empty || self.void.contains(i) || !visited.insert(i) {
+                continue;
+            }
+            // 揭开一个空白棋子会揭开整片相连的空白棋子和它们周围的数字
+            let mut unit = Vec::new();
+            let mut pending = vec![self.coords_of(i)];
+            while let Some(coords) = pending.pop() {
+                unit.push(coords);
+                if self[coords] != Tile::Empty {
+                    continue;
+                }
+                for c in self.neighbors(coords) {
+                    if let Some(j) = self.index_of(c) {
+                        if visited.insert(j) {
+                            pending.push(c);
+                        }
+                    }
+                }
+            }
+            units.push(unit);
+        }
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if let Tile::BombNeighbor(_) = tile {
+                if !visited.contains(i) {
+                    units.push(vec![self.coords_of(i)]);
+                }
+            }
+        }
+        units
+    }
+
+    /// 在棋盘上放置炸弹和邻居，保证坐标`safe`以及它周围的棋子都不是炸弹
+    /// 每个棋子上最多放置`max_per_tile`个炸弹，随机选到已经有炸弹的棋子时再加一个
+    /// 相同的随机数生成器状态、棋盘尺寸、炸弹数量和`safe`总是生成相同的棋盘
+    pub fn set_bombs_excluding(
+        &mut self,
+        bomb_count: u16,
+        max_per_tile: u8,
+        safe: Coordinates,
+        rng: &mut impl Rng,
+    ) -> Result<(), GameError> {
+        let excluded = self.safe_area(safe);
+        self.check_capacity(bomb_count, max_per_tile, &excluded)?;
+        self.place_bombs(
+            bomb_count,
+            max_per_tile,
+            |coords| excluded.contains(&coords),
+            rng,
+        );
+        Ok(())
+    }
+
+    // `safe`及其周围的棋子
+    fn safe_area(&self, safe: Coordinates) -> Vec<Coordinates> {
+        std::iter::once(safe).chain(self.neighbors(safe)).collect()
+    }
+
+    // 除去被排除的坐标和空洞后，剩下的棋子必须放得下所有的炸弹，否则随机放置永远不会结束
+    fn check_capacity(
+        &self,
+        bomb_count: u16,
+        max_per_tile: u8,
+        excluded: &[Coordinates],
+    ) -> Result<(), GameError> {
+        let free = self
+            .tiles()
+            .filter(|(coords, _)| !excluded.contains(coords))
+            .count();
+        let capacity = free as u32 * max_per_tile as u32;
+        if bomb_count as u32 > capacity {
+            return Err(GameError::TooManyMines {
+                bomb_count,
+                limit: capacity + 1,
+            });
+        }
+        Ok(())
+    }
+
+    // 在棋盘上放置炸弹和邻居，跳过被排除的坐标
+    fn place_bombs(
+        &mut self,
+        bomb_count: u16,
+        max_per_tile: u8,
+        excluded: impl Fn(Coordinates) -> bool,
+        rng: &mut impl Rng,
+    ) {
+        self.bomb_count = bomb_count;
+        let mut remaining_bombs = bomb_count;
+
+        // 放置炸弹
+        while remaining_bombs > 0 {
+            // 在棋盘上随机放置一个炸弹
+            let coords = Coordinates {
+                x: rng.gen_range(0..self.width),
+                y: rng.gen_range(0..self.height),
+            };
+            if excluded(coords) {
+                continue;
+            }
+            let index = coords.y as usize * self.width as usize + coords.x as usize;
+            if self.void.contains(index) {
+                continue;
+            }
+            if self.place_bomb(index, max_per_tile) {
+                remaining_bombs -= 1;
+            }
+        }
+
+        self.set_neighbors();
+    }
+
+    /// 在下标处放置一个炸弹，已经有`max_per_tile`个炸弹时返回false
+    fn place_bomb(&mut self, index: usize, max_per_tile: u8) -> bool {
+        let count = self.tiles[index].mines();
+        if count >= max_per_tile {
+            return false;
+        }
+        self.mines.insert(index);
+        self.tiles[index] = Tile::Bomb(count + 1);
+        true
+    }
+
+    /// 放置和炸弹相邻的棋子
+    fn set_neighbors(&mut self) {
+        for i in 0..self.len() {
+            // 如果这个棋子本身就是一个炸弹或者空洞，则跳过
+            if self.mines.contains(i) || self.void.contains(i) {
+                continue;
+            }
+            // 记录这个棋子周围有几个炸弹，改变相邻关系后原来的数字可能变为空白
+            self.tiles[i] = match self.bomb_count_at(self.coords_of(i)) {
+                0 => Tile::Empty,
+                num => Tile::BombNeighbor(num),
+            };
+        }
+    }
+}
+
+impl Index<Coordinates> for TileMap {
+    type Output = Tile;
+
+    /// 坐标不在棋盘内时panic，不确定时使用`get`
+    fn index(&self, coords: Coordinates) -> &Self::Output {
+        match self.index_of(coords) {
+            Some(i) => &self.tiles[i],
+            None => panic!("坐标{}不在棋盘({}, {})内", coords, self.width, self.height),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Coordinates;
+
+/*
+*--------*-------*-------*
+| -1, 1  | 0, 1  | 1, 1  |
+|--------|-------|-------|
+| -1, 0  | tile  | 1, 0  |
+|--------|-------|-------|
+| -1, -1 | 0, -1 | 1, -1 |
+*--------*-------*-------*
+*/
+const SQUARE_COORDINATES: [(i8, i8); 8] = [
+    // 左下
+    (-1, -1),
+    // 下
+    (0, -1),
+    // 右下
+    (1, -1),
+    // 左
+    (-1, 0),
+    // 右
+    (1, 0),
+    // 左上
+    (-1, 1),
+    // 上
+    (0, 1),
+    // 右上
+    (1, 1),
+];
+
+/*
+ 偶数行                      奇数行（向右错开半个棋子）
+   | -1, 1 | 0, 1 |              | 0, 1 | 1, 1 |
+ | -1, 0 | tile | 1, 0 |      | -1, 0 | tile | 1, 0 |
+   | -1,-1 | 0,-1 |              | 0,-1 | 1,-1 |
+*/
+const HEX_EVEN_ROW_COORDINATES: [(i8, i8); 6] = [
+    // 左下
+    (-1, -1),
+    // 右下
+    (0, -1),
+    // 左
+    (-1, 0),
+    // 右
+    (1, 0),
+    // 左上
+    (-1, 1),
+    // 右上
+    (0, 1),
+];
+
+const HEX_ODD_ROW_COORDINATES: [(i8, i8); 6] = [
+    // 左下
+    (0, -1),
+    // 右下
+    (1, -1),
+    // 左
+    (-1, 0),
+    // 右
+    (1, 0),
+    // 左上
+    (0, 1),
+    // 右上
+    (1, 1),
+];
+
+/// 棋子之间的相邻关系
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topology {
+    /// 正方形的棋子，每个棋子有8个邻居
+    #[default]
+    Square,
+    /// 六边形的棋子，每个棋子有6个邻居
+    /// 使用偏移坐标：行和正方形棋盘一样，奇数行向右错开半个棋子
+    Hex,
+}
+
+impl Topology {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Square => "Square",
+            Self::Hex => "Hex",
+        }
+    }
+
+    /// 邻居相对于坐标的偏移量，六边形棋盘中和坐标所在的行有关
+    /// 不检查是否在棋盘内，需要棋盘内的邻居时使用`TileMap::neighbors`
+    pub fn neighbor_offsets(&self, coords: Coordinates) -> &'static [(i8, i8)] {
+        match self {
+            Self::Square => &SQUARE_COORDINATES,
+            Self::Hex if coords.y.is_multiple_of(2) => &HEX_EVEN_ROW_COORDINATES,
+            Self::Hex => &HEX_ODD_ROW_COORDINATES,
+        }
+    }
+
+    /// 从一个棋子走到另一个棋子最少需要经过的步数，每一步移动到一个邻居
+    /// 不考虑棋盘的边缘是否相连，需要时使用`TileMap::distance`
+    pub fn distance(&self, a: Coordinates, b: Coordinates) -> u32 {
+        self.offset_distance((a.x as i64, a.y as i64), (b.x as i64, b.y as i64))
+    }
+
+    /// 同`distance`，坐标可以在棋盘外，用来计算绕过棋盘边缘的距离
+    pub(crate) fn offset_distance(&self, (x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> u32 {
+        match self {
+            Self::Square => (x1 - x2).abs().max((y1 - y2).abs()) as u32,
+            Self::Hex => {
+                // 转为轴坐标：奇数行错开的半个棋子折算到列上
+                let axial = |x: i64, y: i64| (x - (y - (y & 1)) / 2, y);
+                let ((q1, r1), (q2, r2)) = (axial(x1, y1), axial(x2, y2));
+                let (dq, dr) = (q1 - q2, r1 - r2);
+                ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as u32
+            }
+        }
+    }
+}
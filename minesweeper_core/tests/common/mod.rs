@@ -0,0 +1,24 @@
+//! 多个测试共用的棋盘
+#![allow(dead_code)]
+
+use minesweeper_core::TileMap;
+
+/// 中间有一个空洞的环，左上角有一个炸弹
+pub const RING: &str = "
+*...
+.##.
+.##.
+....
+";
+
+/// 左上角的棋子上有3个炸弹，右上角有2个
+pub const STACKED: &str = "
+3...2
+.....
+.....
+";
+
+/// 读取文本格式的棋盘，第一行在最上面
+pub fn board(text: &str) -> TileMap {
+    TileMap::from_text(text).unwrap()
+}
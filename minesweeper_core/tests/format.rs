@@ -0,0 +1,53 @@
+mod common;
+
+use common::{board, RING, STACKED};
+use minesweeper_core::{BoardFormat, Coordinates, FormatError, TileMap};
+
+#[test]
+fn text_and_code_round_trip() {
+    for text in ["*.\n..", RING, STACKED] {
+        let tile_map = board(text);
+        assert_eq!(tile_map.to_text().trim(), text.trim());
+        let code = tile_map.to_code();
+        assert_eq!(
+            TileMap::from_code(&code).unwrap().to_text(),
+            tile_map.to_text()
+        );
+    }
+}
+
+#[test]
+fn mbf_rejects_what_it_cannot_represent() {
+    let plain = board("*.\n..");
+    let data = plain.write(BoardFormat::Mbf).unwrap();
+    assert_eq!(
+        TileMap::read(&data, BoardFormat::Mbf).unwrap().to_text(),
+        plain.to_text()
+    );
+    assert_eq!(
+        board(RING).write(BoardFormat::Mbf),
+        Err(FormatError::VoidUnsupported)
+    );
+    assert_eq!(
+        board(STACKED).write(BoardFormat::Mbf),
+        Err(FormatError::MultiMineUnsupported)
+    );
+}
+
+#[test]
+fn plain_codes_keep_the_old_layout() {
+    // 没有空洞、每个棋子最多一个炸弹的棋盘代码只有头部和炸弹，和以前的代码一样
+    let plain = board("*.\n..");
+    assert_eq!(plain.to_code(), "AAIAAoA");
+    let decoded = TileMap::from_code("AAIAAoA").unwrap();
+    assert_eq!(decoded.void().count(), 0);
+    assert_eq!(decoded.to_text(), plain.to_text());
+}
+
+#[test]
+fn tiles_hold_at_most_max_mines() {
+    assert_eq!(
+        TileMap::from_text("4.").unwrap_err(),
+        FormatError::TooManyMinesInTile(Coordinates { x: 0, y: 0 })
+    );
+}
@@ -0,0 +1,169 @@
+use minesweeper_core::{
+    Coordinates, Game, GameError, GameOutcome, Generation, RevealOutcome, TileMap,
+};
+
+/// 左上角和右下角各有一个炸弹
+const CORNERS: &str = "
+*...
+....
+...*
+";
+
+fn corners() -> Game {
+    Game::from_tile_map(TileMap::from_text(CORNERS).unwrap(), 0)
+}
+
+const TOP_LEFT: Coordinates = Coordinates { x: 0, y: 2 };
+const BOTTOM_RIGHT: Coordinates = Coordinates { x: 3, y: 0 };
+
+#[test]
+fn revealing_an_empty_tile_floods_its_neighbors() {
+    let mut game = corners();
+    let revealed = match game.reveal(Coordinates { x: 0, y: 0 }) {
+        RevealOutcome::Revealed { revealed } => revealed,
+        outcome => panic!("没有揭开棋子: {:?}", outcome),
+    };
+    assert_eq!(revealed[0], Coordinates { x: 0, y: 0 });
+    assert_eq!(revealed.len(), 6);
+    assert!(revealed.iter().all(|c| !game.is_covered_at(*c)));
+    assert!(game.is_covered_at(Coordinates { x: 1, y: 2 }));
+    assert_eq!(game.outcome(), GameOutcome::Playing);
+
+    // 已经揭开的棋子不能再揭开
+    assert_eq!(
+        game.reveal(Coordinates { x: 1, y: 1 }),
+        RevealOutcome::Ignored
+    );
+}
+
+#[test]
+fn revealing_a_mine_ends_the_game() {
+    let mut game = corners();
+    assert_eq!(game.reveal(TOP_LEFT), RevealOutcome::Exploded);
+    assert_eq!(game.outcome(), GameOutcome::Lost(TOP_LEFT));
+    assert_eq!(
+        game.reveal(Coordinates { x: 0, y: 0 }),
+        RevealOutcome::Ignored
+    );
+    assert_eq!(game.toggle_mark(BOTTOM_RIGHT), None);
+}
+
+#[test]
+fn revealing_every_safe_tile_wins_and_flags_the_mines() {
+    let mut game = corners();
+    game.toggle_mark(TOP_LEFT);
+    game.reveal(Coordinates { x: 0, y: 0 });
+    let flagged = match game.reveal(Coordinates { x: 3, y: 2 }) {
+        RevealOutcome::Won { flagged, .. } => flagged,
+        outcome => panic!("没有胜利: {:?}", outcome),
+    };
+    assert_eq!(game.outcome(), GameOutcome::Won);
+    // 已经标记的炸弹不需要再标记
+    assert_eq!(flagged, [BOTTOM_RIGHT]);
+    assert!(game.is_marked_at(TOP_LEFT) && game.is_marked_at(BOTTOM_RIGHT));
+    assert_eq!(game.remaining_mines(), 0);
+}
+
+#[test]
+fn flags_protect_tiles_and_count_down_the_mines() {
+    let mut game = corners();
+    assert_eq!(game.toggle_mark(TOP_LEFT), Some(1));
+    assert_eq!(game.reveal(TOP_LEFT), RevealOutcome::Ignored);
+    assert_eq!(game.remaining_mines(), 1);
+
+    // 标记过多时剩余的炸弹数量为负数
+    game.toggle_mark(Coordinates { x: 1, y: 2 });
+    game.toggle_mark(Coordinates { x: 2, y: 2 });
+    assert_eq!(game.remaining_mines(), -1);
+
+    assert_eq!(game.toggle_mark(TOP_LEFT), Some(0));
+    assert!(!game.is_marked_at(TOP_LEFT));
+
+    game.reveal(Coordinates { x: 0, y: 0 });
+    assert_eq!(game.toggle_mark(Coordinates { x: 0, y: 0 }), None);
+}
+
+#[test]
+fn chord_needs_as_many_flags_as_the_number() {
+    let mut game = corners();
+    game.reveal(Coordinates { x: 0, y: 0 });
+    let number = Coordinates { x: 1, y: 1 };
+    assert!(game.chord(number).is_empty());
+    // 空白棋子和没有揭开的棋子不能双击
+    assert!(game.chord(Coordinates { x: 0, y: 0 }).is_empty());
+    assert!(game.chord(Coordinates { x: 2, y: 2 }).is_empty());
+
+    game.toggle_mark(TOP_LEFT);
+    let mut chorded = game.chord(number);
+    chorded.sort();
+    assert_eq!(
+        chorded,
+        [Coordinates { x: 1, y: 2 }, Coordinates { x: 2, y: 2 }]
+    );
+
+    // 标记错误时双击会揭开炸弹
+    game.toggle_mark(TOP_LEFT);
+    game.toggle_mark(Coordinates { x: 1, y: 2 });
+    assert!(game.chord(number).contains(&TOP_LEFT));
+}
+
+#[test]
+fn too_many_mines_are_rejected_instead_of_hanging() {
+    // 揭开中间的棋子后整个棋盘都是安全区域，没有位置放置炸弹
+    let mut game = Game::new((3, 3), 1, Generation::Random, 0);
+    assert_eq!(
+        game.validate(),
+        Err(GameError::TooManyMines {
+            bomb_count: 1,
+            limit: 0
+        })
+    );
+    assert_eq!(
+        game.reveal(Coordinates { x: 1, y: 1 }),
+        RevealOutcome::Ignored
+    );
+    assert!(!game.is_generated());
+
+    let limit = Game::mine_limit(81, 1);
+    assert_eq!(limit, 72);
+    assert!(Game::new((9, 9), 71, Generation::Random, 0)
+        .validate()
+        .is_ok());
+    assert!(Game::new((9, 9), 72, Generation::Random, 0)
+        .validate()
+        .is_err());
+    assert!(Game::new((9, 9), 72, Generation::Random, 0)
+        .with_mines_per_tile(2)
+        .validate()
+        .is_ok());
+    assert_eq!(
+        Game::new((0, 9), 0, Generation::Random, 0).validate(),
+        Err(GameError::EmptyMap {
+            width: 0,
+            height: 9
+        })
+    );
+}
+
+#[test]
+fn the_fullest_valid_board_can_be_generated() {
+    let mut game = Game::new((9, 9), 71, Generation::Random, 7);
+    game.reveal(Coordinates { x: 4, y: 4 });
+    assert!(game.is_generated());
+    assert!(game.is_valid());
+    assert_eq!(game.tile_map().bomb_count(), 71);
+}
+
+#[test]
+fn seed_and_first_reveal_define_the_board() {
+    let mines = |x, y| {
+        let mut game = Game::new((16, 16), 40, Generation::Random, 42);
+        game.reveal(Coordinates { x, y });
+        game.tile_map().mines().collect::<Vec<_>>()
+    };
+    assert_eq!(mines(3, 3), mines(3, 3));
+    // 落在安全区域的炸弹会重新放置，第一次揭开别的棋子时可能是另一个棋盘
+    let first = mines(3, 3);
+    let moved = first[0];
+    assert_ne!(mines(moved.x, moved.y), first);
+}
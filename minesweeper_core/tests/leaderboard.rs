@@ -0,0 +1,167 @@
+use std::{fs, path::PathBuf};
+
+use minesweeper_core::{Difficulty, Leaderboard, Score, ScoreCategory};
+
+const BEGINNER: ScoreCategory = ScoreCategory::Preset(Difficulty::Beginner);
+
+fn score(category: ScoreCategory, time: f32, seed: u64) -> Score {
+    Score {
+        category,
+        time,
+        bbbv_per_second: 1.0,
+        seed,
+        date: 0,
+    }
+}
+
+fn times(leaderboard: &Leaderboard, category: ScoreCategory) -> Vec<f32> {
+    leaderboard.scores(category).iter().map(|s| s.time).collect()
+}
+
+/// 每个测试使用单独的临时目录
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "minesweeper-leaderboard-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir.join("leaderboard.json")
+}
+
+#[test]
+fn category_matches_presets() {
+    assert_eq!(ScoreCategory::new((9, 9), 10), BEGINNER);
+    assert_eq!(
+        ScoreCategory::new((30, 16), 99),
+        ScoreCategory::Preset(Difficulty::Expert)
+    );
+    assert_eq!(
+        ScoreCategory::new((9, 9), 11),
+        ScoreCategory::Custom {
+            width: 9,
+            height: 9,
+            bomb_count: 11
+        }
+    );
+}
+
+#[test]
+fn scores_are_ranked_by_time() {
+    let mut leaderboard = Leaderboard::default();
+    assert_eq!(leaderboard.record(score(BEGINNER, 30., 1)), Some(0));
+    assert_eq!(leaderboard.record(score(BEGINNER, 10., 2)), Some(0));
+    assert_eq!(leaderboard.record(score(BEGINNER, 20., 3)), Some(1));
+    assert_eq!(times(&leaderboard, BEGINNER), [10., 20., 30.]);
+}
+
+#[test]
+fn ties_keep_the_earlier_score_first() {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.record(score(BEGINNER, 10., 1));
+    assert_eq!(leaderboard.record(score(BEGINNER, 10., 2)), Some(1));
+    let seeds: Vec<u64> = leaderboard
+        .scores(BEGINNER)
+        .iter()
+        .map(|s| s.seed)
+        .collect();
+    assert_eq!(seeds, [1, 2]);
+}
+
+#[test]
+fn only_the_best_scores_are_kept() {
+    let mut leaderboard = Leaderboard::default();
+    for i in 0..Leaderboard::MAX_SCORES {
+        leaderboard.record(score(BEGINNER, 10. + i as f32, i as u64));
+    }
+    assert_eq!(leaderboard.record(score(BEGINNER, 100., 0)), None);
+    assert_eq!(leaderboard.record(score(BEGINNER, 1., 0)), Some(0));
+    let times = times(&leaderboard, BEGINNER);
+    assert_eq!(times.len(), Leaderboard::MAX_SCORES);
+    assert_eq!(times.first(), Some(&1.));
+    assert_eq!(times.last(), Some(&18.));
+}
+
+#[test]
+fn categories_are_ranked_separately() {
+    let custom = ScoreCategory::new((5, 5), 3);
+    let expert = ScoreCategory::Preset(Difficulty::Expert);
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.record(score(custom, 5., 0));
+    leaderboard.record(score(expert, 100., 0));
+    leaderboard.record(score(BEGINNER, 20., 0));
+    assert_eq!(leaderboard.record(score(BEGINNER, 30., 0)), Some(1));
+    assert_eq!(times(&leaderboard, custom), [5.]);
+    assert_eq!(leaderboard.categories(), [BEGINNER, expert, custom]);
+}
+
+#[test]
+fn invalid_scores_are_rejected() {
+    let mut leaderboard = Leaderboard::default();
+    assert_eq!(leaderboard.record(score(BEGINNER, f32::NAN, 0)), None);
+    assert_eq!(leaderboard.record(score(BEGINNER, -1., 0)), None);
+    assert!(leaderboard.categories().is_empty());
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let path = temp_path("round-trip");
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.record(score(BEGINNER, 12.5, 42));
+    leaderboard.save(&path).unwrap();
+    assert_eq!(Leaderboard::load(&path), leaderboard);
+}
+
+#[test]
+fn missing_file_is_empty() {
+    let path = temp_path("missing");
+    assert_eq!(Leaderboard::load(&path), Leaderboard::default());
+}
+
+#[test]
+fn corrupted_file_is_backed_up_and_replaced() {
+    let path = temp_path("corrupted");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{\"scores\": [{\"category\"").unwrap();
+
+    let mut leaderboard = Leaderboard::load(&path);
+    assert_eq!(leaderboard, Leaderboard::default());
+    let backup = path.with_extension("json.corrupt");
+    assert_eq!(
+        fs::read_to_string(&backup).unwrap(),
+        "{\"scores\": [{\"category\""
+    );
+
+    // 损坏之后仍然可以继续记录成绩
+    leaderboard.record(score(BEGINNER, 10., 0));
+    leaderboard.save(&path).unwrap();
+    assert_eq!(times(&Leaderboard::load(&path), BEGINNER), [10.]);
+}
+
+#[test]
+fn invalid_entries_in_the_file_are_dropped() {
+    let path = temp_path("invalid-entries");
+    let mut leaderboard = Leaderboard::default();
+    for i in 0..3 {
+        leaderboard.record(score(BEGINNER, 10. + i as f32, i));
+    }
+    leaderboard.save(&path).unwrap();
+    // 手动改坏一个成绩，并且打乱顺序
+    let data = fs::read_to_string(&path)
+        .unwrap()
+        .replace("\"time\": 10.0", "\"time\": -5.0")
+        .replace("\"time\": 11.0", "\"time\": 99.0");
+    fs::write(&path, data).unwrap();
+
+    assert_eq!(times(&Leaderboard::load(&path), BEGINNER), [12., 99.]);
+}
+
+#[test]
+fn date_is_formatted_in_utc() {
+    let mut s = score(BEGINNER, 1., 0);
+    assert_eq!(s.date_string(), "1970-01-01");
+    s.date = 1_646_092_800; // 2022-03-01
+    assert_eq!(s.date_string(), "2022-03-01");
+    s.date = 951_782_400; // 2000-02-29
+    assert_eq!(s.date_string(), "2000-02-29");
+}
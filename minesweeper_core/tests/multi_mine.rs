@@ -0,0 +1,90 @@
+mod common;
+
+use common::{board, STACKED};
+use minesweeper_core::{Coordinates, Game, GameOutcome, Generation, RevealOutcome, Tile};
+use proptest::prelude::*;
+
+#[test]
+fn numbers_count_every_mine() {
+    let tile_map = board(STACKED);
+    assert_eq!(tile_map.bomb_count(), 5);
+    assert_eq!(tile_map.mine_tile_count(), 2);
+    assert_eq!(tile_map[Coordinates { x: 0, y: 2 }], Tile::Bomb(3));
+    assert_eq!(tile_map.mines_at(Coordinates { x: 4, y: 2 }), 2);
+    assert_eq!(tile_map[Coordinates { x: 1, y: 1 }], Tile::BombNeighbor(3));
+    assert_eq!(tile_map[Coordinates { x: 3, y: 1 }], Tile::BombNeighbor(2));
+    assert!(tile_map.is_valid());
+}
+
+#[test]
+fn flags_cycle_up_to_the_mines_per_tile() {
+    let mut game = Game::from_tile_map(board(STACKED), 0);
+    assert_eq!(game.mines_per_tile(), 3);
+    let corner = Coordinates { x: 0, y: 2 };
+    let counts: Vec<Option<u8>> = (0..4).map(|_| game.toggle_mark(corner)).collect();
+    assert_eq!(counts, [Some(1), Some(2), Some(3), Some(0)]);
+    assert!(!game.is_marked_at(corner));
+
+    game.toggle_mark(corner);
+    game.toggle_mark(corner);
+    assert_eq!(game.mark_count_at(corner), 2);
+    assert_eq!(game.remaining_mines(), 3);
+    assert!(game.is_valid());
+}
+
+#[test]
+fn chord_counts_flags_on_each_tile() {
+    let mut game = Game::from_tile_map(board(STACKED), 0);
+    let number = Coordinates { x: 1, y: 1 };
+    let corner = Coordinates { x: 0, y: 2 };
+    game.reveal(number);
+    game.toggle_mark(corner);
+    game.toggle_mark(corner);
+    assert!(game.chord(number).is_empty());
+
+    game.toggle_mark(corner);
+    assert_eq!(game.chord(number).len(), 7);
+}
+
+#[test]
+fn winning_corrects_the_flag_counts() {
+    let mut game = Game::from_tile_map(board(STACKED), 0);
+    let corner = Coordinates { x: 0, y: 2 };
+    game.toggle_mark(corner);
+
+    let flagged = match game.reveal(Coordinates { x: 2, y: 0 }) {
+        RevealOutcome::Won { flagged, .. } => flagged,
+        outcome => panic!("没有胜利: {:?}", outcome),
+    };
+    assert_eq!(game.outcome(), GameOutcome::Won);
+    assert_eq!(flagged, [corner, Coordinates { x: 4, y: 2 }]);
+    assert_eq!(game.mark_count_at(corner), 3);
+    assert_eq!(game.remaining_mines(), 0);
+}
+
+proptest! {
+    #[test]
+    fn generated_tiles_hold_up_to_the_limit(
+        seed in any::<u64>(),
+        bomb_count in 0u16..=150,
+        mines_per_tile in 1u8..=3,
+    ) {
+        let bomb_count = bomb_count.min(55 * mines_per_tile as u16);
+        let mut game = Game::new((8, 8), bomb_count, Generation::Random, seed)
+            .with_mines_per_tile(mines_per_tile);
+        game.reveal(Coordinates { x: 4, y: 4 });
+
+        let tile_map = game.tile_map();
+        prop_assert!(game.is_valid());
+        prop_assert_eq!(tile_map.bomb_count(), bomb_count);
+        let total: u16 = tile_map.tiles().map(|(_, t)| t.mines() as u16).sum();
+        prop_assert_eq!(total, bomb_count);
+        for (coords, tile) in tile_map.tiles() {
+            prop_assert!(tile.mines() <= mines_per_tile);
+            if !tile.is_bomb() {
+                let around: u8 = tile_map.neighbors(coords).map(|c| tile_map.mines_at(c)).sum();
+                prop_assert_eq!(tile_map.bomb_count_at(coords), around);
+            }
+        }
+    }
+}
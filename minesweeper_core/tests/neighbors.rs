@@ -0,0 +1,258 @@
+use std::collections::{HashSet, VecDeque};
+
+use minesweeper_core::{Coordinates, Tile, TileMap, Topology};
+use proptest::prelude::*;
+
+/// 棋盘尺寸和棋盘内的一个坐标
+fn board_and_coords() -> impl Strategy<Value = ((u16, u16), Coordinates)> {
+    (1u16..=64, 1u16..=64).prop_flat_map(|(width, height)| {
+        (0..width, 0..height).prop_map(move |(x, y)| ((width, height), Coordinates { x, y }))
+    })
+}
+
+/// 不依赖`neighbors`，直接计算棋盘内邻居的数量
+fn expected_neighbor_count((width, height): (u16, u16), coords: Coordinates) -> usize {
+    let span = |v: u16, size: u16| (v.saturating_sub(1)..=(v + 1).min(size - 1)).count();
+    span(coords.x, width) * span(coords.y, height) - 1
+}
+
+fn is_adjacent(a: Coordinates, b: Coordinates) -> bool {
+    a != b && a.x.abs_diff(b.x) <= 1 && a.y.abs_diff(b.y) <= 1
+}
+
+fn topology() -> impl Strategy<Value = Topology> {
+    prop_oneof![Just(Topology::Square), Just(Topology::Hex)]
+}
+
+/// 空棋盘，边缘相连的六边形棋盘高度必须是偶数
+fn shaped(width: u16, height: u16, topology: Topology, wrap: bool) -> TileMap {
+    let height = match topology {
+        Topology::Hex if wrap && height % 2 == 1 => height + 1,
+        _ => height,
+    };
+    TileMap::empty(width, height)
+        .with_topology(topology)
+        .with_wrap(wrap)
+}
+
+/// 在棋盘上广度优先搜索，得到从`start`到每个棋子的步数
+fn steps_from(tile_map: &TileMap, start: Coordinates) -> Vec<(Coordinates, u32)> {
+    let mut steps = vec![(start, 0)];
+    let mut visited = HashSet::from([start]);
+    let mut pending = VecDeque::from([(start, 0)]);
+    while let Some((coords, n)) = pending.pop_front() {
+        for c in tile_map.neighbors(coords) {
+            if visited.insert(c) {
+                steps.push((c, n + 1));
+                pending.push_back((c, n + 1));
+            }
+        }
+    }
+    steps
+}
+
+proptest! {
+    #[test]
+    fn checked_offset_matches_signed_arithmetic(
+        x in any::<u16>(),
+        y in any::<u16>(),
+        dx in any::<i8>(),
+        dy in any::<i8>(),
+    ) {
+        let (nx, ny) = (x as i32 + dx as i32, y as i32 + dy as i32);
+        let in_range = |v: i32| (0..=u16::MAX as i32).contains(&v);
+        let expected = (in_range(nx) && in_range(ny)).then_some(Coordinates {
+            x: nx as u16,
+            y: ny as u16,
+        });
+        prop_assert_eq!(Coordinates { x, y }.checked_offset((dx, dy)), expected);
+    }
+
+    #[test]
+    fn neighbors_are_adjacent_and_in_bounds(((width, height), coords) in board_and_coords()) {
+        let tile_map = TileMap::empty(width, height);
+        let neighbors: Vec<Coordinates> = tile_map.neighbors(coords).collect();
+        let unique: HashSet<Coordinates> = neighbors.iter().copied().collect();
+
+        prop_assert_eq!(unique.len(), neighbors.len(), "重复的邻居: {:?}", neighbors);
+        prop_assert_eq!(neighbors.len(), expected_neighbor_count((width, height), coords));
+        for n in neighbors {
+            prop_assert!(n.x < width && n.y < height, "{} 的邻居 {} 不在棋盘内", coords, n);
+            prop_assert!(is_adjacent(coords, n), "{} 和 {} 不相邻", coords, n);
+        }
+    }
+
+    #[test]
+    fn neighbors_are_symmetric(((width, height), coords) in board_and_coords()) {
+        let tile_map = TileMap::empty(width, height);
+        for n in tile_map.neighbors(coords) {
+            prop_assert!(tile_map.neighbors(n).any(|c| c == coords));
+        }
+    }
+
+    #[test]
+    fn bomb_counts_only_see_real_neighbors(
+        (width, height, mines) in (1u16..=16, 1u16..=16).prop_flat_map(|(w, h)| {
+            let cells = w as usize * h as usize;
+            (Just(w), Just(h), proptest::collection::btree_set(0..cells, 0..=cells))
+        })
+    ) {
+        let mines: Vec<Coordinates> = mines
+            .into_iter()
+            .map(|i| Coordinates { x: (i % width as usize) as u16, y: (i / width as usize) as u16 })
+            .collect();
+        let tile_map = TileMap::from_mines(width, height, mines.iter().copied()).unwrap();
+        for (coords, tile) in tile_map.tiles() {
+            let expected = mines.iter().filter(|m| is_adjacent(coords, **m)).count() as u8;
+            match *tile {
+                Tile::Bomb(_) => prop_assert!(mines.contains(&coords)),
+                Tile::Empty => prop_assert_eq!(expected, 0, "{}", coords),
+                Tile::BombNeighbor(count) => prop_assert_eq!(count, expected, "{}", coords),
+            }
+        }
+    }
+
+    #[test]
+    fn hex_neighbors_are_in_bounds_and_symmetric(((width, height), coords) in board_and_coords()) {
+        let tile_map = TileMap::empty(width, height).with_topology(Topology::Hex);
+        let neighbors: Vec<Coordinates> = tile_map.neighbors(coords).collect();
+        let unique: HashSet<Coordinates> = neighbors.iter().copied().collect();
+
+        prop_assert_eq!(unique.len(), neighbors.len(), "重复的邻居: {:?}", neighbors);
+        prop_assert!(neighbors.len() <= 6);
+        for n in neighbors {
+            prop_assert!(n.x < width && n.y < height, "{} 的邻居 {} 不在棋盘内", coords, n);
+            prop_assert!(is_adjacent(coords, n), "{} 和 {} 不相邻", coords, n);
+            prop_assert!(tile_map.neighbors(n).any(|c| c == coords));
+        }
+    }
+
+    #[test]
+    fn distance_counts_steps_between_tiles(
+        topology in topology(),
+        wrap in any::<bool>(),
+        ((width, height), start) in board_and_coords(),
+    ) {
+        let tile_map = shaped(width.min(16), height.min(16), topology, wrap);
+        let start = Coordinates {
+            x: start.x.min(tile_map.width() - 1),
+            y: start.y.min(tile_map.height() - 1),
+        };
+        for (coords, steps) in steps_from(&tile_map, start) {
+            prop_assert_eq!(tile_map.distance(start, coords), steps, "{} -> {}", start, coords);
+            prop_assert_eq!(tile_map.distance(coords, start), steps);
+            if !wrap {
+                prop_assert_eq!(topology.distance(start, coords), steps);
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_neighbors_are_complete_and_symmetric(
+        topology in topology(),
+        ((width, height), coords) in board_and_coords(),
+    ) {
+        let tile_map = shaped(width, height, topology, true);
+        let neighbors: Vec<Coordinates> = tile_map.neighbors(coords).collect();
+        let unique: HashSet<Coordinates> = neighbors.iter().copied().collect();
+
+        prop_assert_eq!(unique.len(), neighbors.len(), "重复的邻居: {:?}", neighbors);
+        prop_assert!(!unique.contains(&coords));
+        if tile_map.width() >= 3 && tile_map.height() >= 3 {
+            let full = match topology {
+                Topology::Square => 8,
+                Topology::Hex => 6,
+            };
+            prop_assert_eq!(neighbors.len(), full);
+        }
+        for n in neighbors {
+            prop_assert!(n.x < tile_map.width() && n.y < tile_map.height());
+            prop_assert!(tile_map.neighbors(n).any(|c| c == coords), "{} -> {}", coords, n);
+        }
+    }
+}
+
+#[test]
+fn interior_hex_tiles_have_six_neighbors() {
+    let tile_map = TileMap::empty(5, 5).with_topology(Topology::Hex);
+    for y in 1..4 {
+        for x in 1..4 {
+            assert_eq!(tile_map.neighbors(Coordinates { x, y }).count(), 6);
+        }
+    }
+    // 偶数行的左边和奇数行的右边只缺一个邻居
+    assert_eq!(tile_map.neighbors(Coordinates { x: 0, y: 2 }).count(), 3);
+    assert_eq!(tile_map.neighbors(Coordinates { x: 0, y: 1 }).count(), 5);
+    assert_eq!(tile_map.neighbors(Coordinates { x: 4, y: 1 }).count(), 3);
+    assert_eq!(tile_map.neighbors(Coordinates { x: 4, y: 2 }).count(), 5);
+}
+
+#[test]
+fn changing_topology_recounts_bombs() {
+    let mines = [Coordinates { x: 0, y: 0 }];
+    let square = TileMap::from_mines(3, 3, mines).unwrap();
+    assert_eq!(square[Coordinates { x: 1, y: 1 }], Tile::BombNeighbor(1));
+
+    // 奇数行向右错开，(1, 1)和(0, 0)不再相邻
+    let hex = square.with_topology(Topology::Hex);
+    assert_eq!(hex.topology(), Topology::Hex);
+    assert_eq!(hex[Coordinates { x: 1, y: 1 }], Tile::Empty);
+    assert_eq!(hex[Coordinates { x: 0, y: 1 }], Tile::BombNeighbor(1));
+    assert_eq!(hex[Coordinates { x: 1, y: 0 }], Tile::BombNeighbor(1));
+    assert!(hex.is_bomb_at(Coordinates { x: 0, y: 0 }));
+}
+
+#[test]
+fn wrapped_bomb_counts_cross_the_edges() {
+    let mines = [Coordinates { x: 0, y: 0 }];
+    let tile_map = TileMap::from_mines(5, 5, mines).unwrap().with_wrap(true);
+    assert!(tile_map.wraps());
+    for (x, y) in [(4, 4), (4, 0), (0, 4), (1, 4), (4, 1), (1, 1)] {
+        assert_eq!(
+            tile_map[Coordinates { x, y }],
+            Tile::BombNeighbor(1),
+            "({}, {})",
+            x,
+            y
+        );
+    }
+    assert_eq!(tile_map[Coordinates { x: 2, y: 2 }], Tile::Empty);
+    assert_eq!(tile_map[Coordinates { x: 3, y: 3 }], Tile::Empty);
+
+    // 取消相连后，对边的数字重新变为空白
+    let tile_map = tile_map.with_wrap(false);
+    assert_eq!(tile_map[Coordinates { x: 4, y: 4 }], Tile::Empty);
+}
+
+#[test]
+fn corners_have_three_neighbors() {
+    let tile_map = TileMap::empty(9, 9);
+    for (x, y) in [(0, 0), (8, 0), (0, 8), (8, 8)] {
+        assert_eq!(tile_map.neighbors(Coordinates { x, y }).count(), 3);
+    }
+}
+
+#[test]
+fn single_tile_has_no_neighbors() {
+    let tile_map = TileMap::empty(1, 1);
+    assert_eq!(tile_map.neighbors(Coordinates { x: 0, y: 0 }).count(), 0);
+}
+
+#[test]
+fn offsets_at_the_limits_do_not_wrap() {
+    let origin = Coordinates { x: 0, y: 0 };
+    assert_eq!(origin.checked_offset((-1, 0)), None);
+    assert_eq!(origin.checked_offset((0, -1)), None);
+    let max = Coordinates {
+        x: u16::MAX,
+        y: u16::MAX,
+    };
+    assert_eq!(max.checked_offset((1, 0)), None);
+    assert_eq!(
+        max.checked_offset((-1, -1)),
+        Some(Coordinates {
+            x: u16::MAX - 1,
+            y: u16::MAX - 1
+        })
+    );
+}
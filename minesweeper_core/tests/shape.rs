@@ -0,0 +1,85 @@
+mod common;
+
+use common::{board, RING};
+use minesweeper_core::{
+    Coordinates, FormatError, Game, GameOutcome, Generation, RevealOutcome, Tile,
+};
+use proptest::prelude::*;
+
+#[test]
+fn void_cells_are_not_tiles_or_neighbors() {
+    let tile_map = board(RING);
+    assert_eq!(tile_map.tile_count(), 12);
+    assert_eq!(tile_map.tiles().count(), 12);
+    assert_eq!(tile_map.void().count(), 4);
+    assert!(tile_map.is_void_at(Coordinates { x: 1, y: 1 }));
+    assert!(!tile_map.is_void_at(Coordinates { x: 0, y: 0 }));
+
+    let neighbors: Vec<Coordinates> = tile_map.neighbors(Coordinates { x: 0, y: 1 }).collect();
+    assert_eq!(
+        neighbors,
+        [
+            Coordinates { x: 0, y: 0 },
+            Coordinates { x: 1, y: 0 },
+            Coordinates { x: 0, y: 2 },
+        ]
+    );
+    assert_eq!(tile_map[Coordinates { x: 0, y: 2 }], Tile::BombNeighbor(1));
+}
+
+#[test]
+fn mines_cannot_be_placed_in_void_cells() {
+    let corner = Coordinates { x: 0, y: 3 };
+    assert_eq!(
+        board(RING).with_void([corner]).unwrap_err(),
+        FormatError::MineInVoid(corner)
+    );
+    let outside = Coordinates { x: 4, y: 0 };
+    assert_eq!(
+        board(RING).with_void([outside]).unwrap_err(),
+        FormatError::VoidOutOfBounds(outside)
+    );
+}
+
+#[test]
+fn completion_ignores_void_cells() {
+    let mut game = Game::from_tile_map(board(RING), 0);
+    assert!(game.is_valid());
+    assert_eq!(game.covered().count(), 12);
+
+    // 空洞不能揭开也不能标记
+    assert_eq!(
+        game.reveal(Coordinates { x: 1, y: 1 }),
+        RevealOutcome::Ignored
+    );
+    assert_eq!(game.toggle_mark(Coordinates { x: 1, y: 1 }), None);
+
+    game.reveal(Coordinates { x: 3, y: 0 });
+    assert_eq!(game.outcome(), GameOutcome::Won);
+    assert_eq!(
+        game.covered().collect::<Vec<_>>(),
+        [Coordinates { x: 0, y: 3 }]
+    );
+}
+
+proptest! {
+    #[test]
+    fn generated_mines_avoid_void_cells(
+        seed in any::<u64>(),
+        bomb_count in 0u16..=8,
+        x in 0u16..8,
+    ) {
+        // 中间两行是空洞，上下各剩下两行
+        let void = (2..4).flat_map(|y| (0..8).map(move |x| Coordinates { x, y }));
+        let mut game = Game::new((8, 6), bomb_count, Generation::Random, seed)
+            .with_void(void)
+            .unwrap();
+        game.reveal(Coordinates { x, y: 0 });
+
+        let tile_map = game.tile_map();
+        prop_assert!(game.is_valid());
+        prop_assert_eq!(tile_map.mines().count(), bomb_count as usize);
+        prop_assert!(tile_map.mines().all(|c| !tile_map.is_void_at(c)));
+        prop_assert!(tile_map.void().all(|c| tile_map[c] == Tile::Empty));
+    }
+}
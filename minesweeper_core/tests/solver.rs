@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use minesweeper_core::{
+    solver::{self, Knowledge, Solver, MAX_COMPONENT_CELLS},
+    Coordinates, Game, Generation, Tile, TileMap,
+};
+use proptest::prelude::*;
+
+fn board(text: &str) -> TileMap {
+    TileMap::from_text(text).unwrap()
+}
+
+fn at(x: u16, y: u16) -> Coordinates {
+    Coordinates { x, y }
+}
+
+#[test]
+fn single_constraints_find_mines_and_safe_tiles() {
+    let tile_map = board("**.\n...");
+    let mut solver = Solver::new(&tile_map);
+    solver.reveal(at(0, 0));
+    solver.reveal(at(1, 0));
+
+    // 左下角的2周围只剩两个棋子，都是炸弹；之后中间的2周围剩下的棋子都是安全的
+    assert!(solver.solve());
+    assert!(solver.is_mine(at(0, 1)) && solver.is_mine(at(1, 1)));
+    assert!(solver.is_revealed(at(2, 0)) && solver.is_revealed(at(2, 1)));
+}
+
+#[test]
+fn subset_constraints_solve_one_two_one() {
+    let tile_map = board("*.*\n...");
+    let mut solver = Solver::new(&tile_map);
+    for x in 0..3 {
+        solver.reveal(at(x, 0));
+    }
+
+    // 1-2-1：每个1都是2的子集，2多出来的棋子是炸弹
+    assert!(solver.solve());
+    assert!(solver.is_mine(at(0, 1)) && solver.is_mine(at(2, 1)));
+    assert!(solver.is_revealed(at(1, 1)));
+}
+
+#[test]
+fn the_mine_count_resolves_unconstrained_tiles() {
+    // 最左边的棋子周围没有揭开的数字，只能根据剩余的炸弹数量判断
+    let tile_map = board(".*..");
+    let mut solver = Solver::new(&tile_map);
+    solver.reveal(at(3, 0));
+    assert!(solver.solve());
+    assert!(solver.is_mine(at(1, 0)));
+    assert!(solver.is_revealed(at(0, 0)));
+}
+
+#[test]
+fn a_fifty_fifty_is_not_solvable() {
+    let tile_map = board("*..\n...");
+    assert!(!solver::is_solvable(&tile_map, at(2, 0)));
+
+    let mut solver = Solver::new(&tile_map);
+    solver.reveal(at(2, 0));
+    assert!(!solver.solve());
+    assert!(!solver.is_mine(at(0, 1)) && !solver.is_revealed(at(0, 0)));
+}
+
+#[test]
+fn no_guess_boards_are_solvable_from_the_first_click() {
+    let start = at(4, 4);
+    for seed in 0..20 {
+        let mut game = Game::new((9, 9), 10, Generation::NoGuess { max_attempts: 1000 }, seed);
+        game.reveal(start);
+        assert!(
+            solver::is_solvable(game.tile_map(), start),
+            "种子{}生成的棋盘需要猜测",
+            seed
+        );
+    }
+}
+
+fn knowledge(game: &Game) -> Knowledge<'_> {
+    Knowledge::new(game.tile_map(), game.covered(), game.marked())
+}
+
+/// 不依赖求解器，枚举未知棋子上所有的炸弹分布，计算每个棋子是炸弹的概率
+/// 标记的棋子视为炸弹，没有满足条件的分布时返回`None`
+fn brute_force(game: &Game) -> Option<HashMap<Coordinates, f64>> {
+    let tile_map = game.tile_map();
+    let unknown: Vec<Coordinates> = game.covered().filter(|c| !game.is_marked_at(*c)).collect();
+    let remaining = (tile_map.bomb_count() as usize).checked_sub(game.marked().count())?;
+    let numbers: Vec<(Coordinates, usize)> = tile_map
+        .tiles()
+        .filter(|(c, _)| !game.is_covered_at(*c))
+        .filter_map(|(c, tile)| match tile {
+            Tile::Empty => Some((c, 0)),
+            Tile::BombNeighbor(n) => Some((c, *n as usize)),
+            Tile::Bomb(_) => None,
+        })
+        .collect();
+
+    let mut total = 0u32;
+    let mut counts = vec![0u32; unknown.len()];
+    for mask in 0u32..1 << unknown.len() {
+        if mask.count_ones() as usize != remaining {
+            continue;
+        }
+        let is_mine = |c: &Coordinates| {
+            game.is_marked_at(*c)
+                || unknown
+                    .iter()
+                    .position(|u| u == c)
+                    .is_some_and(|i| mask >> i & 1 == 1)
+        };
+        let consistent = numbers
+            .iter()
+            .all(|(c, n)| tile_map.neighbors(*c).filter(is_mine).count() == *n);
+        if !consistent {
+            continue;
+        }
+        total += 1;
+        for (i, count) in counts.iter_mut().enumerate() {
+            *count += mask >> i & 1;
+        }
+    }
+    if total == 0 {
+        return None;
+    }
+    Some(
+        unknown
+            .into_iter()
+            .zip(counts)
+            .map(|(c, n)| (c, n as f64 / total as f64))
+            .collect(),
+    )
+}
+
+#[test]
+fn the_mine_count_decides_floating_tiles() {
+    let mut game = Game::from_tile_map(board(".*.."), 0);
+    game.reveal(at(3, 0));
+    let analysis = knowledge(&game).analyze().unwrap();
+    assert!(!analysis.approximate);
+    assert_eq!(analysis.mines, [at(1, 0)]);
+    // 最左边的棋子不和任何数字相邻，唯一的炸弹已经找到，所以它是安全的
+    assert_eq!(analysis.safe, [at(0, 0)]);
+}
+
+#[test]
+fn large_components_fall_back_to_an_approximation() {
+    // 中间一行交替放置炸弹，揭开最下面一行后中间一行是一组很大的约束
+    let width = MAX_COMPONENT_CELLS + 6;
+    let text = format!(
+        "{}\n{}\n{}",
+        ".".repeat(width),
+        "*.".repeat(width / 2),
+        ".".repeat(width)
+    );
+    let mut game = Game::from_tile_map(board(&text), 0);
+    for x in 0..width as u16 {
+        game.reveal(at(x, 0));
+    }
+
+    let analysis = knowledge(&game).analyze().unwrap();
+    assert!(analysis.approximate);
+    assert_eq!(analysis.probabilities.len(), width * 2);
+    assert!(analysis
+        .probabilities
+        .values()
+        .all(|p| (0.0..=1.0).contains(p)));
+    assert!(analysis.safe.iter().all(|c| !game.is_bomb_at(*c)));
+    assert!(analysis.mines.iter().all(|c| game.is_bomb_at(*c)));
+}
+
+proptest! {
+    #[test]
+    fn probabilities_match_brute_force(
+        width in 2u16..=4,
+        height in 2u16..=4,
+        bomb_count in 1u16..=6,
+        seed in any::<u64>(),
+        start in (0u16..4, 0u16..4),
+        reveals in prop::collection::vec((0u16..4, 0u16..4), 0..3),
+        marks in prop::collection::vec((0u16..4, 0u16..4), 0..3),
+    ) {
+        let mut game = Game::new((width, height), bomb_count, Generation::Random, seed);
+        game.reveal(at(start.0 % width, start.1 % height));
+        prop_assume!(game.is_generated());
+        for (x, y) in reveals {
+            let coords = at(x % width, y % height);
+            if !game.is_bomb_at(coords) {
+                game.reveal(coords);
+            }
+        }
+        // 标记可能是错误的，这时两边都应该发现矛盾
+        for (x, y) in marks {
+            game.toggle_mark(at(x % width, y % height));
+        }
+
+        let analysis = knowledge(&game).analyze();
+        let expected = brute_force(&game);
+        prop_assert_eq!(analysis.is_some(), expected.is_some());
+        let (analysis, expected) = match (analysis, expected) {
+            (Some(a), Some(e)) => (a, e),
+            _ => return Ok(()),
+        };
+        prop_assert!(!analysis.approximate);
+        prop_assert_eq!(analysis.probabilities.len(), expected.len());
+        for (coords, p) in expected.iter() {
+            let actual = analysis.probability(*coords).unwrap();
+            prop_assert!((actual - p).abs() < 1e-9, "{}: {} != {}", coords, actual, p);
+            prop_assert_eq!(analysis.safe.contains(coords), *p == 0.0);
+            prop_assert_eq!(analysis.mines.contains(coords), *p == 1.0);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use minesweeper_core::{
+    Coordinates, Difficulty, Game, GameOutcome, Generation, Leaderboard, RevealOutcome, Score,
+    ScoreCategory,
+};
+
+/// 终端中的一局游戏：游戏状态、光标位置和计时
+pub struct App {
+    pub difficulty: Difficulty,
+    /// 指定的随机数种子，为`None`时每局随机选择
+    /// 炸弹避开第一次揭开的棋子，第一次揭开同一个棋子才能得到相同的棋盘
+    seed: Option<u64>,
+    pub game: Game,
+    pub cursor: Coordinates,
+    /// 第一次揭开棋子的时间
+    started: Option<Instant>,
+    /// 游戏结束时的用时
+    finished: Option<Duration>,
+    /// 和图形界面共用的排行榜
+    leaderboard: Leaderboard,
+    /// 这一局在排行榜中的名次
+    pub rank: Option<usize>,
+}
+
+impl App {
+    pub fn new(difficulty: Difficulty, seed: Option<u64>) -> Self {
+        let (width, height) = difficulty.map_size();
+        Self {
+            difficulty,
+            seed,
+            game: Self::new_game(difficulty, seed),
+            cursor: Coordinates {
+                x: width / 2,
+                y: height / 2,
+            },
+            started: None,
+            finished: None,
+            leaderboard: Leaderboard::default_path()
+                .map(Leaderboard::load)
+                .unwrap_or_default(),
+            rank: None,
+        }
+    }
+
+    fn new_game(difficulty: Difficulty, seed: Option<u64>) -> Game {
+        Game::new(
+            difficulty.map_size(),
+            difficulty.bomb_count(),
+            Generation::Random,
+            seed.unwrap_or_else(rand::random),
+        )
+    }
+
+    /// 重新开始，保留光标位置
+    pub fn restart(&mut self) {
+        self.game = Self::new_game(self.difficulty, self.seed);
+        self.started = None;
+        self.finished = None;
+        self.rank = None;
+    }
+
+    /// 移动光标，不会移出棋盘
+    pub fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let tile_map = self.game.tile_map();
+        let x = (self.cursor.x as i32 + dx).clamp(0, tile_map.width() as i32 - 1);
+        let y = (self.cursor.y as i32 + dy).clamp(0, tile_map.height() as i32 - 1);
+        self.cursor = Coordinates {
+            x: x as u16,
+            y: y as u16,
+        };
+    }
+
+    /// 揭开光标处的棋子，如果已经揭开则双击
+    pub fn reveal(&mut self) {
+        if self.game.is_covered_at(self.cursor) {
+            self.reveal_all(vec![self.cursor]);
+        } else {
+            self.chord();
+        }
+    }
+
+    /// 双击光标处的数字棋子
+    pub fn chord(&mut self) {
+        let tiles = self.game.chord(self.cursor);
+        self.reveal_all(tiles);
+    }
+
+    /// 标记或者取消标记光标处的棋子
+    pub fn toggle_mark(&mut self) {
+        self.game.toggle_mark(self.cursor);
+    }
+
+    /// 揭开所有的棋子，空白棋子周围的棋子由`Game::reveal`一起揭开
+    fn reveal_all(&mut self, pending: Vec<Coordinates>) {
+        if pending.is_empty() {
+            return;
+        }
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+        for coords in pending {
+            match self.game.reveal(coords) {
+                RevealOutcome::Ignored | RevealOutcome::Revealed { .. } => (),
+                RevealOutcome::Exploded | RevealOutcome::Won { .. } => break,
+            }
+        }
+        // 只在游戏刚刚结束的时候停止计时和记录成绩
+        if self.game.is_playing() || self.finished.is_some() {
+            return;
+        }
+        self.finished = Some(self.elapsed());
+        if self.game.outcome() == GameOutcome::Won {
+            self.record_score();
+        }
+    }
+
+    /// 胜利时记录成绩，进入排行榜时保存
+    fn record_score(&mut self) {
+        let time = self.elapsed().as_secs_f32();
+        let bbbv = self.game.tile_map().bbbv() as f32;
+        let bbbv_per_second = if time > 0. { bbbv / time } else { 0. };
+        let score = Score::now(
+            ScoreCategory::Preset(self.difficulty),
+            time,
+            bbbv_per_second,
+            self.game.seed(),
+        );
+        self.rank = self.leaderboard.record(score);
+        if self.rank.is_none() {
+            return;
+        }
+        if let Some(path) = Leaderboard::default_path() {
+            // 终端处于原始模式，保存失败时不打断游戏
+            let _ = self.leaderboard.save(path);
+        }
+    }
+
+    /// 游戏进行的时间，从第一次揭开棋子开始计时，游戏结束时停止
+    pub fn elapsed(&self) -> Duration {
+        match (self.finished, self.started) {
+            (Some(finished), _) => finished,
+            (None, Some(started)) => started.elapsed(),
+            (None, None) => Duration::ZERO,
+        }
+    }
+}
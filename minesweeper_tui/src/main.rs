@@ -0,0 +1,104 @@
+//! 在终端中玩扫雷，适合没有图形界面的环境
+//!
+//! 用法: minesweeper_tui [beginner|intermediate|expert] [--seed <种子>]
+
+use std::{
+    env,
+    io::{self, Write},
+    process,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyEvent},
+    execute,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use minesweeper_core::Difficulty;
+
+use crate::app::App;
+
+mod app;
+mod ui;
+
+/// 没有按键的时候也要刷新计时器
+const TICK: Duration = Duration::from_millis(200);
+
+fn main() {
+    let (difficulty, seed) = match parse_args(env::args().skip(1)) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("用法: minesweeper_tui [beginner|intermediate|expert] [--seed <种子>]");
+            process::exit(2);
+        }
+    };
+
+    let mut stdout = io::stdout();
+    if let Err(e) = run(&mut stdout, App::new(difficulty, seed)) {
+        eprintln!("终端出错: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<(Difficulty, Option<u64>), String> {
+    let mut difficulty = Difficulty::Beginner;
+    let mut seed = None;
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed 需要一个数字")?;
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("无效的种子: {}", value))?,
+                );
+            }
+            name => {
+                difficulty = Difficulty::ALL
+                    .into_iter()
+                    .find(|d| d.name().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("未知的难度: {}", name))?;
+            }
+        }
+    }
+    Ok((difficulty, seed))
+}
+
+/// 进入终端的原始模式运行游戏，退出时恢复终端
+fn run(out: &mut impl Write, app: App) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)?;
+    let result = event_loop(out, app);
+    execute!(out, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn event_loop(out: &mut impl Write, mut app: App) -> io::Result<()> {
+    loop {
+        ui::draw(out, &app)?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let code = match event::read()? {
+            Event::Key(KeyEvent { code, .. }) => code,
+            _ => continue,
+        };
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            // y 轴向上，和图形界面一致
+            KeyCode::Up | KeyCode::Char('k') => app.move_cursor(0, 1),
+            KeyCode::Down | KeyCode::Char('j') => app.move_cursor(0, -1),
+            KeyCode::Left | KeyCode::Char('h') => app.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => app.move_cursor(1, 0),
+            KeyCode::Char(' ') | KeyCode::Enter => app.reveal(),
+            KeyCode::Char('f') => app.toggle_mark(),
+            KeyCode::Char('c') => app.chord(),
+            KeyCode::Char('r') => app.restart(),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{Clear, ClearType},
+};
+use minesweeper_core::{Coordinates, GameOutcome, Tile};
+
+use crate::app::App;
+
+const HELP: &str = "方向键/hjkl 移动  空格 揭开  f 标记  c 双击  r 重新开始  q 退出";
+
+/// 绘制整个画面：标题、棋盘、状态栏和帮助
+pub fn draw(out: &mut impl Write, app: &App) -> io::Result<()> {
+    let game = &app.game;
+    let tile_map = game.tile_map();
+    let (width, height) = (tile_map.width(), tile_map.height());
+
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(format!(
+            "Mine Sweeper - {} (seed: {})",
+            app.difficulty.name(),
+            game.seed()
+        ))
+    )?;
+
+    // 每个棋子占两列，看起来更接近正方形
+    let border: String = format!("+{}+", "-".repeat(width as usize * 2 + 1));
+    queue!(out, MoveTo(0, 1), Print(&border))?;
+    // 和图形界面一样，y 轴向上，所以从最上面一行开始画
+    for (row, y) in (0..height).rev().enumerate() {
+        queue!(out, MoveTo(0, row as u16 + 2), Print("| "))?;
+        for x in 0..width {
+            let coords = Coordinates { x, y };
+            if coords == app.cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            draw_tile(out, app, coords)?;
+            queue!(out, SetAttribute(Attribute::Reset), ResetColor, Print(' '))?;
+        }
+        queue!(out, Print('|'))?;
+    }
+    queue!(out, MoveTo(0, height + 2), Print(&border))?;
+
+    let face = match (game.outcome(), app.rank) {
+        (GameOutcome::Playing, _) => ":)".to_string(),
+        (GameOutcome::Won, Some(rank)) => format!("B) 你赢了！排行榜第{}名", rank + 1),
+        (GameOutcome::Won, None) => "B) 你赢了！".to_string(),
+        (GameOutcome::Lost(_), _) => ":( 你输了！".to_string(),
+    };
+    queue!(
+        out,
+        MoveTo(0, height + 3),
+        Print(format!(
+            "Mines: {:03}  Time: {:03}  {}",
+            game.remaining_mines(),
+            app.elapsed().as_secs(),
+            face
+        )),
+        MoveTo(0, height + 4),
+        Print(HELP),
+    )?;
+    out.flush()
+}
+
+fn draw_tile(out: &mut impl Write, app: &App, coords: Coordinates) -> io::Result<()> {
+    let game = &app.game;
+    let lost = match game.outcome() {
+        GameOutcome::Lost(exploded) => Some(exploded),
+        _ => None,
+    };
+    let tile = game.tile_map()[coords];
+
+    if game.is_covered_at(coords) {
+        let bomb = tile.is_bomb();
+        return match (game.is_marked_at(coords), lost.is_some()) {
+            // 失败后标出错误的标记
+            (true, true) if !bomb => queue!(out, SetForegroundColor(Color::Magenta), Print('X')),
+            (true, _) => queue!(out, SetForegroundColor(Color::Red), Print('F')),
+            // 失败后揭开剩下的炸弹
+            (false, true) if bomb => queue!(out, Print(tile.symbol())),
+            (false, _) => queue!(out, SetForegroundColor(Color::DarkGrey), Print('#')),
+        };
+    }
+
+    if lost == Some(coords) {
+        queue!(out, SetBackgroundColor(Color::Red))?;
+    }
+    let color = match tile {
+        Tile::BombNeighbor(1) => Color::Cyan,
+        Tile::BombNeighbor(2) => Color::Green,
+        Tile::BombNeighbor(3) => Color::Yellow,
+        Tile::BombNeighbor(_) | Tile::Bomb(_) => Color::Red,
+        Tile::Empty => Color::Reset,
+    };
+    queue!(out, SetForegroundColor(color), Print(tile.symbol()))
+}
@@ -0,0 +1,146 @@
+use bevy::{log, prelude::*};
+use board_plugin::resources::board::{Board, GameOutcome};
+
+use crate::AppState;
+
+/// 显示剩余炸弹数量、计时器和重新开始按钮
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_hud).add_system(update_hud);
+        // 新的棋盘创建之前，清除上一局的炸弹数量和时间
+        for state in [AppState::InGame, AppState::Refresh] {
+            app.add_system_set(SystemSet::on_enter(state).with_system(reset_hud));
+        }
+        // 只有有棋盘的时候才能重新开始，菜单和排行榜中不响应
+        for state in [AppState::InGame, AppState::Paused, AppState::GameOver] {
+            app.add_system_set(SystemSet::on_update(state).with_system(restart_button));
+        }
+    }
+}
+
+/// HUD 上需要更新的文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum HudText {
+    /// 剩余的炸弹数量
+    Mines,
+    /// 游戏进行的秒数
+    Timer,
+    /// 重新开始按钮上的表情
+    Face,
+}
+
+impl HudText {
+    /// 还没有棋盘时显示的文字
+    fn initial(self) -> &'static str {
+        match self {
+            HudText::Mines | HudText::Timer => "000",
+            HudText::Face => ":)",
+        }
+    }
+}
+
+/// 重新开始按钮
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+struct RestartButton;
+
+fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/pixeled.ttf");
+    let text = |kind: HudText| TextBundle {
+        text: Text::with_section(
+            kind.initial(),
+            TextStyle {
+                font: font.clone(),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+
+    commands.spawn_bundle(UiCameraBundle::default());
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Px(50.0)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::SpaceAround,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("HUD"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(text(HudText::Mines))
+                .insert(HudText::Mines);
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(60.0), Val::Px(40.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::DARK_GRAY.into(),
+                    ..Default::default()
+                })
+                .insert(RestartButton)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(text(HudText::Face))
+                        .insert(HudText::Face);
+                });
+            parent
+                .spawn_bundle(text(HudText::Timer))
+                .insert(HudText::Timer);
+        });
+}
+
+fn update_hud(board: Option<Res<Board>>, mut texts: Query<(&mut Text, &HudText)>) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for (mut text, kind) in texts.iter_mut() {
+        text.sections[0].value = match kind {
+            HudText::Mines => format!("{:03}", board.remaining_mines()),
+            HudText::Timer => format!("{:03}", board.elapsed as u32),
+            HudText::Face => match board.game.outcome() {
+                GameOutcome::Playing => ":)",
+                GameOutcome::Won => "B)",
+                GameOutcome::Lost(_) => ":(",
+            }
+            .to_string(),
+        };
+    }
+}
+
+fn reset_hud(mut texts: Query<(&mut Text, &HudText)>) {
+    for (mut text, kind) in texts.iter_mut() {
+        text.sections[0].value = kind.initial().to_string();
+    }
+}
+
+fn restart_button(
+    mut state: ResMut<State<AppState>>,
+    interactions: Query<&Interaction, (Changed<Interaction>, With<RestartButton>)>,
+) {
+    for interaction in interactions.iter() {
+        if let Interaction::Clicked = interaction {
+            log::info!("重载游戏");
+            if let Err(e) = state.replace(AppState::Refresh) {
+                log::error!("重载游戏失败: {:?}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use bevy::{log, prelude::*};
+use board_plugin::resources::{
+    board::Board, BoardShape, BoardSource, GameStats, ReplayPlayer, Topology,
+};
+use minesweeper_core::{Leaderboard, Score, ScoreCategory};
+
+use crate::AppState;
+
+/// 用户数据目录不可用时，排行榜保存在当前目录
+const LEADERBOARD_FILE: &str = "leaderboard.json";
+
+/// 记录每个难度的最好成绩，并且在菜单中显示排行榜
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        let path = leaderboard_path();
+        log::info!("排行榜: {}", path.display());
+        app.insert_resource(Leaderboard::load(path))
+            .add_system(record_score)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Leaderboard).with_system(setup_leaderboard),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Leaderboard).with_system(back_button))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Leaderboard).with_system(cleanup_leaderboard),
+            );
+    }
+}
+
+fn leaderboard_path() -> PathBuf {
+    Leaderboard::default_path().unwrap_or_else(|| PathBuf::from(LEADERBOARD_FILE))
+}
+
+/// 返回菜单的按钮
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+struct BackButton;
+
+/// 排行榜界面的根节点
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+struct LeaderboardScreen;
+
+/// 胜利时按照棋盘配置记录成绩
+fn record_score(
+    board: Option<Res<Board>>,
+    player: Option<Res<ReplayPlayer>>,
+    mut leaderboard: ResMut<Leaderboard>,
+    mut game_stats_evr: EventReader<GameStats>,
+) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    for stats in game_stats_evr.iter() {
+        // 回放的录像不是玩家自己的成绩，从文件读取的棋盘每次都一样，也不计入排行榜
+        // 排行榜只按照棋盘大小和炸弹数量分类，暂时只记录边缘不相连的正方形棋盘的成绩
+        if !stats.won
+            || player.is_some()
+            || matches!(board.options.source, BoardSource::File(_))
+            || board.options.topology != Topology::Square
+            || board.options.wrap
+            || matches!(board.options.shape, BoardShape::Mask(_))
+            || board.options.mines_per_tile > 1
+        {
+            continue;
+        }
+        let category = ScoreCategory::new(board.options.map_size, board.options.bomb_count);
+        let score = Score::now(
+            category,
+            stats.time,
+            stats.bbbv_per_second,
+            board.game.seed(),
+        );
+        match leaderboard.record(score) {
+            Some(rank) => log::info!("{} 第{}名!", category.name(), rank + 1),
+            None => continue,
+        }
+        if let Err(e) = leaderboard.save(leaderboard_path()) {
+            log::error!("保存排行榜失败: {}", e);
+        }
+    }
+}
+
+fn setup_leaderboard(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    leaderboard: Res<Leaderboard>,
+) {
+    let font = asset_server.load("fonts/pixeled.ttf");
+    let text = |value: &str, font_size: f32| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+            Default::default(),
+        ),
+        style: Style {
+            margin: Rect::all(Val::Px(3.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::BLACK.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Leaderboard"))
+        .insert(LeaderboardScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("Leaderboard", 40.0));
+
+            let categories = leaderboard.categories();
+            if categories.is_empty() {
+                parent.spawn_bundle(text("No scores yet", 16.0));
+            }
+            for category in categories {
+                parent.spawn_bundle(text(&category.name(), 20.0));
+                for (rank, score) in leaderboard.scores(category).into_iter().enumerate() {
+                    let line = format!(
+                        "{:>2}. {:>7.2}s  {:>5.2} 3BV/s  {}  #{}",
+                        rank + 1,
+                        score.time,
+                        score.bbbv_per_second,
+                        score.date_string(),
+                        score.seed
+                    );
+                    parent.spawn_bundle(text(&line, 10.0));
+                }
+            }
+
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(200.0), Val::Px(40.0)),
+                        margin: Rect::all(Val::Px(10.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    color: Color::DARK_GRAY.into(),
+                    ..Default::default()
+                })
+                .insert(BackButton)
+                .with_children(|parent| {
+                    parent.spawn_bundle(text("Back", 16.0));
+                });
+        });
+}
+
+fn back_button(
+    mut state: ResMut<State<AppState>>,
+    interactions: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+) {
+    if interactions.iter().any(|i| *i == Interaction::Clicked) {
+        if let Err(e) = state.set(AppState::Menu) {
+            log::error!("返回菜单失败: {:?}", e);
+        }
+    }
+}
+
+fn cleanup_leaderboard(mut commands: Commands, screens: Query<Entity, With<LeaderboardScreen>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,17 +1,40 @@
-use bevy::{log, prelude::*};
+use std::{env, fs, io};
+
+use bevy::{log, prelude::*, window::WindowCloseRequested};
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 use board_plugin::{
-    resources::{BoardAssets, BoardOptions, SpriteMaterial, TileSize},
+    events::BoardConfigErrorEvent,
+    resources::{
+        board::{Board, GameOutcome},
+        BoardAssets, BoardOptions, Replay, ReplayPlayer, ReplayRecorder, SaveGame, SpriteMaterial,
+        TileSize,
+    },
     BoardPlugin,
 };
+use hud::HudPlugin;
+use leaderboard::LeaderboardPlugin;
+use menu::MenuPlugin;
+
+mod hud;
+mod leaderboard;
+mod menu;
+
+/// 未完成的游戏保存在这里，下次启动时继续
+const SAVE_FILE: &str = "savegame.json";
+/// 最近一局游戏的录像，使用 `--replay <文件>` 回放
+const REPLAY_FILE: &str = "replay.json";
+/// 按 E 导出当前的棋盘，可以通过 `BoardSource::File` 再次读取
+const EXPORT_FILE: &str = "board.txt";
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AppState {
+    Menu,
     InGame,
-    Out,
     Paused,
     Refresh,
+    GameOver,
+    Leaderboard,
 }
 
 fn main() {
@@ -27,17 +50,67 @@ fn main() {
     #[cfg(feature = "debug")]
     app.add_plugin(WorldInspectorPlugin::new());
 
+    // 回放录像，或者有存档时直接继续上一次没有完成的游戏
+    let args: Vec<String> = env::args().collect();
+    let replay = args
+        .iter()
+        .position(|a| a == "--replay")
+        .map(|i| args.get(i + 1).map_or(REPLAY_FILE, |p| p.as_str()));
+    let initial_state = match replay.map(Replay::load) {
+        Some(Ok(replay)) => {
+            log::info!("回放录像，1/2/4 切换速度，S 切换单步回放，N 下一步");
+            app.insert_resource(ReplayPlayer::new(replay));
+            AppState::InGame
+        }
+        Some(Err(e)) => {
+            log::error!("无法读取录像: {}", e);
+            AppState::Menu
+        }
+        None => load_save_game(&mut app),
+    };
+
     app.add_startup_system(setup_board)
-        .add_state(AppState::Out)
+        .add_state(initial_state)
         .add_system(state_handler)
+        .add_system(board_config_error_handler)
+        .add_system(save_on_exit)
+        .add_system(export_board)
+        .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(save_game))
+        .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(discard_save_game))
+        .add_system_set(
+            SystemSet::on_enter(AppState::GameOver)
+                .with_system(game_over)
+                .with_system(save_replay),
+        )
         .add_plugin(BoardPlugin {
             running_state: AppState::InGame,
-        });
+            end_state: AppState::GameOver,
+        })
+        .add_plugin(HudPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(LeaderboardPlugin);
     app.add_startup_system(camera_setup);
 
     app.run();
 }
 
+/// 读取存档，有存档时直接进入游戏
+fn load_save_game(app: &mut App) -> AppState {
+    match SaveGame::load(SAVE_FILE) {
+        Ok(save) => {
+            log::info!("读取存档: {}", SAVE_FILE);
+            app.insert_resource(save);
+            AppState::InGame
+        }
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("无法读取存档: {}", e);
+            }
+            AppState::Menu
+        }
+    }
+}
+
 fn camera_setup(mut commands: Commands) {
     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
 }
@@ -50,10 +123,19 @@ fn state_handler(mut state: ResMut<State<AppState>>, keys: Res<Input<KeyCode>>)
     keys.get_just_pressed().for_each(|code| match code {
         KeyCode::R => {
             log::info!("重载游戏");
-            state.set(AppState::Refresh).unwrap();
+            // 替换整个状态栈，确保退出游戏状态的时候清理棋盘
+            state.replace(AppState::Refresh).unwrap();
+        }
+        KeyCode::M if state.current() != &AppState::Menu => {
+            log::info!("返回菜单");
+            state.replace(AppState::Menu).unwrap();
         }
         KeyCode::Escape | KeyCode::Space => {
             let current = state.current();
+            // 只有游戏中才能暂停
+            if current != &AppState::InGame && current != &AppState::Paused {
+                return;
+            }
             if current == &AppState::Paused {
                 log::info!("继续游戏");
             } else {
@@ -69,23 +151,135 @@ fn state_handler(mut state: ResMut<State<AppState>>, keys: Res<Input<KeyCode>>)
     });
 }
 
+/// 棋盘配置无效的时候返回菜单
+fn board_config_error_handler(
+    mut state: ResMut<State<AppState>>,
+    mut board_config_error_evr: EventReader<BoardConfigErrorEvent>,
+) {
+    for event in board_config_error_evr.iter() {
+        log::error!("无法开始游戏: {}", event.0);
+        if state.current() != &AppState::Menu {
+            state.replace(AppState::Menu).unwrap();
+        }
+    }
+}
+
+fn game_over(board: Option<Res<Board>>, player: Option<Res<ReplayPlayer>>) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    match board.game.outcome() {
+        GameOutcome::Won => log::info!("你赢了！按R重新开始"),
+        _ => log::info!("你输了！按R重新开始"),
+    }
+    // 结束的游戏不需要继续
+    discard_save_game(player);
+}
+
+/// 删除存档，回放的录像和存档无关
+/// 重新开始或者返回菜单时离开了游戏状态，存档中的游戏已经被放弃，不能在下次启动时继续
+fn discard_save_game(player: Option<Res<ReplayPlayer>>) {
+    if player.is_some() {
+        return;
+    }
+    if let Err(e) = fs::remove_file(SAVE_FILE) {
+        if e.kind() != io::ErrorKind::NotFound {
+            log::warn!("删除存档失败: {}", e);
+        }
+    }
+}
+
+/// 暂停时保存游戏进度
+fn save_game(board: Option<Res<Board>>, player: Option<Res<ReplayPlayer>>) {
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    // 回放的录像不是玩家自己的游戏
+    if player.is_some() {
+        return;
+    }
+    // 还没有开始或者已经结束的游戏不需要保存
+    if !board.game.is_generated() || !board.is_playing() {
+        return;
+    }
+    match SaveGame::from_board(&board).save(SAVE_FILE) {
+        Ok(()) => log::info!("游戏已保存: {}", SAVE_FILE),
+        Err(e) => log::error!("保存游戏失败: {}", e),
+    }
+}
+
+/// 关闭窗口时保存游戏进度
+fn save_on_exit(
+    board: Option<Res<Board>>,
+    player: Option<Res<ReplayPlayer>>,
+    mut close_evr: EventReader<WindowCloseRequested>,
+) {
+    if close_evr.iter().count() > 0 {
+        save_game(board, player);
+    }
+}
+
+/// 导出当前棋盘的炸弹位置，同时输出可以分享的棋盘代码
+fn export_board(keys: Res<Input<KeyCode>>, board: Option<Res<Board>>) {
+    if !keys.just_pressed(KeyCode::E) {
+        return;
+    }
+    let board = match board {
+        Some(b) => b,
+        None => return,
+    };
+    // 游戏中导出会泄露炸弹的位置，只能在游戏结束之后导出
+    if board.is_playing() {
+        log::warn!("游戏结束后才能导出棋盘");
+        return;
+    }
+    let tile_map = board.game.tile_map();
+    match fs::write(EXPORT_FILE, tile_map.to_text()) {
+        Ok(()) => log::info!("棋盘已导出: {}", EXPORT_FILE),
+        Err(e) => log::error!("导出棋盘失败: {}", e),
+    }
+    log::info!("棋盘代码: {}", tile_map.to_code());
+}
+
+/// 游戏结束时保存这局游戏的录像
+fn save_replay(recorder: Option<Res<ReplayRecorder>>, player: Option<Res<ReplayPlayer>>) {
+    let recorder = match recorder {
+        Some(r) => r,
+        None => return,
+    };
+    if player.is_some() {
+        return;
+    }
+    match recorder.replay.save(REPLAY_FILE) {
+        Ok(()) => log::info!("录像已保存: {}", REPLAY_FILE),
+        Err(e) => log::error!("保存录像失败: {}", e),
+    }
+}
+
 fn setup_board(
     mut commands: Commands,
-    mut state: ResMut<State<AppState>>,
     asset_server: Res<AssetServer>,
+    save_game: Option<Res<SaveGame>>,
+    player: Option<Res<ReplayPlayer>>,
 ) {
-    let size: u16 = 50;
-    commands.insert_resource(BoardOptions {
-        map_size: (size, size),
-        bomb_count: size * 10,
-        tile_padding: 1.0,
-        safe_start: false,
-        tile_size: TileSize::Adaptive {
-            min: 10.0,
-            max: 50.0,
+    // 棋盘大小和炸弹数量在菜单中选择，回放录像或者从存档继续时使用它们的配置
+    let options = match (player, save_game) {
+        (Some(player), _) => player.replay.options.clone(),
+        (None, Some(save)) => save.options.clone(),
+        (None, None) => BoardOptions {
+            tile_padding: 1.0,
+            safe_start: false,
+            reveal_delay: 0.02,
+            tile_size: TileSize::Adaptive {
+                min: 10.0,
+                max: 50.0,
+            },
+            ..Default::default()
         },
-        ..Default::default()
-    });
+    };
+    commands.insert_resource(options);
     commands.insert_resource(BoardAssets {
         label: "Default".to_string(),
         board_material: SpriteMaterial {
@@ -110,6 +304,17 @@ fn setup_board(
             texture: asset_server.load("sprites/bomb.png"),
             color: Color::GRAY,
         },
+        hint_material: SpriteMaterial {
+            color: Color::rgba(0.0, 1.0, 0.0, 0.5),
+            ..Default::default()
+        },
+        exploded_material: SpriteMaterial {
+            color: Color::RED,
+            ..Default::default()
+        },
+        wrong_flag_material: SpriteMaterial {
+            color: Color::RED,
+            ..Default::default()
+        },
     });
-    state.set(AppState::InGame).unwrap();
 }
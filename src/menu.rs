@@ -0,0 +1,366 @@
+use bevy::{log, prelude::*};
+use board_plugin::resources::{BoardOptions, Difficulty, ReplayPlayer, Tile, Topology};
+
+use crate::AppState;
+
+/// 开始菜单，选择难度或者自定义棋盘
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CustomBoard::default())
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(setup_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::Menu)
+                    .with_system(menu_buttons)
+                    .with_system(update_menu_texts),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(cleanup_menu));
+    }
+}
+
+/// 自定义棋盘的参数
+#[derive(Debug, Clone, Copy)]
+struct CustomBoard {
+    width: u16,
+    height: u16,
+    bomb_count: u16,
+}
+
+impl Default for CustomBoard {
+    fn default() -> Self {
+        Self {
+            width: 16,
+            height: 16,
+            bomb_count: 40,
+        }
+    }
+}
+
+/// 自定义棋盘的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Width,
+    Height,
+    BombCount,
+}
+
+impl Field {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Width => "Width",
+            Self::Height => "Height",
+            Self::BombCount => "Mines",
+        }
+    }
+
+    fn value(&self, custom: &CustomBoard) -> u16 {
+        match self {
+            Self::Width => custom.width,
+            Self::Height => custom.height,
+            Self::BombCount => custom.bomb_count,
+        }
+    }
+
+    fn adjust(&self, custom: &mut CustomBoard, delta: i32) {
+        let (value, min, max) = match self {
+            Self::Width => (&mut custom.width, 5, 100),
+            Self::Height => (&mut custom.height, 5, 100),
+            Self::BombCount => (&mut custom.bomb_count, 1, 9999),
+        };
+        *value = (*value as i32 + delta).clamp(min, max) as u16;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum MenuButton {
+    /// 选择难度
+    Preset(Difficulty),
+    /// 调整自定义棋盘的参数
+    Adjust(Field, i32),
+    /// 使用自定义棋盘开始游戏
+    Custom,
+    /// 切换正方形或者六边形的棋子
+    Topology,
+    /// 切换棋盘的边缘是否相连
+    Wrap,
+    /// 切换每个棋子上最多的炸弹数量
+    MinesPerTile,
+    /// 查看排行榜
+    Leaderboard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum MenuText {
+    /// 自定义棋盘参数的值
+    Value(Field),
+    /// 自定义棋盘参数的错误提示
+    Error,
+    /// 当前棋子的形状
+    Topology,
+    /// 棋盘的边缘是否相连
+    Wrap,
+    /// 每个棋子上最多的炸弹数量
+    MinesPerTile,
+}
+
+/// 菜单的根节点
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+struct Menu;
+
+fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut options: ResMut<BoardOptions>,
+    player: Option<Res<ReplayPlayer>>,
+) {
+    // 离开回放后，新的游戏由玩家操作，也不再使用录像的种子
+    if player.is_some() {
+        commands.remove_resource::<ReplayPlayer>();
+        options.seed = None;
+    }
+
+    let font = asset_server.load("fonts/pixeled.ttf");
+    let text = |value: &str, font_size: f32| TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+            Default::default(),
+        ),
+        style: Style {
+            margin: Rect::all(Val::Px(5.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let button = |width: f32| ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(width), Val::Px(40.0)),
+            margin: Rect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::DARK_GRAY.into(),
+        ..Default::default()
+    };
+    let row = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::NONE.into(),
+        ..Default::default()
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                // bevy 的 UI 坐标从下往上，ColumnReverse 才是从上往下排列
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::BLACK.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Menu"))
+        .insert(Menu)
+        .with_children(|parent| {
+            parent.spawn_bundle(text("Mine Sweeper", 40.0));
+
+            for difficulty in Difficulty::ALL {
+                let (width, height) = difficulty.map_size();
+                let label = format!(
+                    "{} {}x{} / {}",
+                    difficulty.name(),
+                    width,
+                    height,
+                    difficulty.bomb_count()
+                );
+                parent
+                    .spawn_bundle(button(400.0))
+                    .insert(MenuButton::Preset(difficulty))
+                    .with_children(|parent| {
+                        parent.spawn_bundle(text(&label, 16.0));
+                    });
+            }
+
+            for field in [Field::Width, Field::Height, Field::BombCount] {
+                parent.spawn_bundle(row()).with_children(|parent| {
+                    parent.spawn_bundle(text(field.name(), 16.0));
+                    for delta in [-10, -1] {
+                        parent
+                            .spawn_bundle(button(50.0))
+                            .insert(MenuButton::Adjust(field, delta))
+                            .with_children(|parent| {
+                                parent.spawn_bundle(text(&delta.to_string(), 12.0));
+                            });
+                    }
+                    parent
+                        .spawn_bundle(text("", 16.0))
+                        .insert(MenuText::Value(field));
+                    for delta in [1, 10] {
+                        parent
+                            .spawn_bundle(button(50.0))
+                            .insert(MenuButton::Adjust(field, delta))
+                            .with_children(|parent| {
+                                parent.spawn_bundle(text(&format!("+{}", delta), 12.0));
+                            });
+                    }
+                });
+            }
+
+            parent
+                .spawn_bundle(button(400.0))
+                .insert(MenuButton::Custom)
+                .with_children(|parent| {
+                    parent.spawn_bundle(text("Custom", 16.0));
+                });
+            parent.spawn_bundle(text("", 12.0)).insert(MenuText::Error);
+
+            parent
+                .spawn_bundle(button(400.0))
+                .insert(MenuButton::Topology)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(text("", 16.0))
+                        .insert(MenuText::Topology);
+                });
+            parent
+                .spawn_bundle(button(400.0))
+                .insert(MenuButton::Wrap)
+                .with_children(|parent| {
+                    parent.spawn_bundle(text("", 16.0)).insert(MenuText::Wrap);
+                });
+            parent
+                .spawn_bundle(button(400.0))
+                .insert(MenuButton::MinesPerTile)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(text("", 16.0))
+                        .insert(MenuText::MinesPerTile);
+                });
+
+            parent
+                .spawn_bundle(button(400.0))
+                .insert(MenuButton::Leaderboard)
+                .with_children(|parent| {
+                    parent.spawn_bundle(text("Leaderboard", 16.0));
+                });
+        });
+}
+
+fn menu_buttons(
+    mut state: ResMut<State<AppState>>,
+    mut options: ResMut<BoardOptions>,
+    mut custom: ResMut<CustomBoard>,
+    interactions: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in interactions.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        let preset = match button {
+            MenuButton::Preset(difficulty) => {
+                log::info!("选择难度: {}", difficulty.name());
+                BoardOptions::from(*difficulty)
+            }
+            MenuButton::Adjust(field, delta) => {
+                field.adjust(&mut custom, *delta);
+                continue;
+            }
+            MenuButton::Custom => {
+                match BoardOptions::custom(
+                    custom.width,
+                    custom.height,
+                    custom.bomb_count,
+                    options.mines_per_tile,
+                ) {
+                    Ok(o) => o,
+                    Err(e) => {
+                        log::warn!("自定义棋盘无效: {}", e);
+                        continue;
+                    }
+                }
+            }
+            MenuButton::Topology => {
+                options.topology = match options.topology {
+                    Topology::Square => Topology::Hex,
+                    Topology::Hex => Topology::Square,
+                };
+                log::info!("棋子形状: {}", options.topology.name());
+                continue;
+            }
+            MenuButton::Wrap => {
+                options.wrap = !options.wrap;
+                log::info!("边缘相连: {}", options.wrap);
+                continue;
+            }
+            MenuButton::MinesPerTile => {
+                options.mines_per_tile = options.mines_per_tile % Tile::MAX_MINES + 1;
+                log::info!("每个棋子最多的炸弹数量: {}", options.mines_per_tile);
+                continue;
+            }
+            MenuButton::Leaderboard => {
+                if let Err(e) = state.set(AppState::Leaderboard) {
+                    log::error!("打开排行榜失败: {:?}", e);
+                }
+                return;
+            }
+        };
+
+        // 只修改棋盘大小和炸弹数量，保留棋子形状、边缘是否相连、每个棋子的炸弹数量和其他的显示配置
+        let selected = BoardOptions {
+            map_size: preset.map_size,
+            bomb_count: preset.bomb_count,
+            ..options.clone()
+        };
+        // 比如边缘相连的六边形棋盘高度必须是偶数，留在菜单中重新选择
+        if let Err(e) = selected.validate() {
+            log::warn!("无法使用这个棋盘: {}", e);
+            continue;
+        }
+        *options = selected;
+        if let Err(e) = state.set(AppState::InGame) {
+            log::error!("开始游戏失败: {:?}", e);
+        }
+        return;
+    }
+}
+
+fn update_menu_texts(
+    custom: Res<CustomBoard>,
+    options: Res<BoardOptions>,
+    mut texts: Query<(&mut Text, &MenuText)>,
+) {
+    let limit = BoardOptions::bomb_limit(custom.width, custom.height, options.mines_per_tile);
+    for (mut text, kind) in texts.iter_mut() {
+        text.sections[0].value = match kind {
+            MenuText::Value(field) => format!("{:>4}", field.value(&custom)),
+            MenuText::Error if custom.bomb_count as u32 >= limit => {
+                format!("Mines must be less than {}", limit)
+            }
+            MenuText::Error => String::new(),
+            MenuText::Topology => format!("Tiles: {}", options.topology.name()),
+            MenuText::Wrap if options.wrap => "Wrap: On".to_string(),
+            MenuText::Wrap => "Wrap: Off".to_string(),
+            MenuText::MinesPerTile => format!("Mines per tile: {}", options.mines_per_tile),
+        };
+    }
+}
+
+fn cleanup_menu(mut commands: Commands, menus: Query<Entity, With<Menu>>) {
+    for entity in menus.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}